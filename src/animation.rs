@@ -0,0 +1,224 @@
+use std::{str::FromStr, sync::OnceLock, time::Duration};
+
+use bevy::prelude::*;
+
+use chess_core::board::TilePos;
+
+use crate::display::BoardOrientation;
+
+pub const DEFAULT_ANIMATION_DURATION: Duration = Duration::from_millis(200);
+
+/// Depth of a piece while it is moving, so it passes over the pieces which aren't
+const MOVING_PIECE_Z: f32 = 1.5;
+
+/// The animation duration and easing, set by the ``--animation-ms`` and ``--easing`` arguments
+static ANIMATION_OPTIONS: OnceLock<(Duration, Easing)> = OnceLock::new();
+
+/// How the progress of an animation is mapped onto the distance travelled
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Easing {
+    Linear,
+    EaseIn,
+    EaseOut,
+    #[default]
+    EaseInOut,
+}
+
+impl FromStr for Easing {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "linear" => Ok(Self::Linear),
+            "ease-in" => Ok(Self::EaseIn),
+            "ease-out" => Ok(Self::EaseOut),
+            "ease-in-out" => Ok(Self::EaseInOut),
+            _ => Err(format!(
+                "Unknown easing \"{s}\", expected linear, ease-in, ease-out, or ease-in-out"
+            )),
+        }
+    }
+}
+
+impl Easing {
+    /// Ease ``t`` (From 0 to 1) using a cubic curve
+    #[must_use]
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0., 1.);
+
+        match self {
+            Self::Linear => t,
+            Self::EaseIn => t * t * t,
+            Self::EaseOut => 1. - (1. - t).powi(3),
+            Self::EaseInOut => {
+                if t < 0.5 {
+                    4. * t * t * t
+                } else {
+                    1. - (-2.0f32).mul_add(t, 2.).powi(3) / 2.
+                }
+            }
+        }
+    }
+}
+
+/// Animate pieces over ``duration`` (Or the default) using ``easing``, this can only be set once
+/// # Errors
+/// Returns an error if the animation options have already been set
+pub fn set_animation_options(duration: Option<Duration>, easing: Option<Easing>) -> Result<(), String> {
+    ANIMATION_OPTIONS
+        .set((duration.unwrap_or(DEFAULT_ANIMATION_DURATION), easing.unwrap_or_default()))
+        .map_err(|_| String::from("Animation options have already been set"))
+}
+
+/// How long a piece takes to move, fade out, or fade in
+#[must_use]
+pub fn animation_duration() -> Duration {
+    ANIMATION_OPTIONS
+        .get()
+        .map_or(DEFAULT_ANIMATION_DURATION, |(duration, _)| *duration)
+}
+
+#[must_use]
+pub fn animation_easing() -> Easing {
+    ANIMATION_OPTIONS.get().map(|(_, easing)| *easing).unwrap_or_default()
+}
+
+/// A piece sliding to its tile
+///
+/// The target is kept as a tile rather than a position, so the animation follows the board when it is flipped
+#[derive(Component, Debug, Clone, Copy)]
+pub struct PieceAnimation {
+    pub from: Vec2,
+    pub to: TilePos,
+    pub elapsed: Duration,
+}
+
+/// A captured piece which is despawned once it has faded out
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct FadeOut {
+    pub elapsed: Duration,
+}
+
+/// A piece which was put back on the board by an undo
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct FadeIn {
+    pub elapsed: Duration,
+}
+
+/// Slide a piece from where it is currently drawn to ``pos``, restarting from its current position if it is already moving
+/// # Panics
+/// Panics if the piece entity's transform can't be found
+pub fn animate_piece_entity(
+    commands: &mut Commands,
+    transform_query: &mut Query<&mut Transform>,
+    piece_entity: Entity,
+    pos: TilePos,
+    orientation: BoardOrientation,
+) {
+    let mut transform = transform_query
+        .get_mut(piece_entity)
+        .unwrap_or_else(|e| panic!("Could get piece entity at pos {pos}\n\t{e:?}"));
+
+    if animation_duration().is_zero() {
+        let (x, y) = orientation.board_to_pixel_coords(pos.file, pos.rank);
+        transform.translation = Vec3::new(x, y, 1.);
+        return;
+    }
+
+    commands.entity(piece_entity).insert(PieceAnimation {
+        from: transform.translation.xy(),
+        to: pos,
+        elapsed: Duration::ZERO,
+    });
+}
+
+/// Fade out a captured piece, which stops it from being dragged while it is still visible
+pub fn fade_out_piece_entity(commands: &mut Commands, piece_entity: Entity) {
+    if animation_duration().is_zero() {
+        commands.entity(piece_entity).despawn();
+        return;
+    }
+
+    commands
+        .entity(piece_entity)
+        .remove::<PieceAnimation>()
+        .insert((FadeOut::default(), Pickable::IGNORE));
+}
+
+/// Fade in a newly spawned piece, starting it transparent so it never appears at full opacity
+pub fn fade_in_piece_entity(entity: &mut EntityCommands) {
+    if animation_duration().is_zero() {
+        return;
+    }
+
+    entity
+        .insert(FadeIn::default())
+        .entry::<Sprite>()
+        .and_modify(|mut sprite| sprite.color.set_alpha(0.));
+}
+
+/// Jump a piece to the end of its animation, so that it can be dragged from its tile
+pub fn finish_piece_animation(
+    commands: &mut Commands,
+    transform: &mut Transform,
+    piece_entity: Entity,
+    animation: &PieceAnimation,
+    orientation: BoardOrientation,
+) {
+    let (x, y) = orientation.board_to_pixel_coords(animation.to.file, animation.to.rank);
+    transform.translation = Vec3::new(x, y, 1.);
+
+    commands.entity(piece_entity).remove::<PieceAnimation>();
+}
+
+/// Progress every moving and fading piece
+#[allow(clippy::needless_pass_by_value)]
+#[allow(clippy::type_complexity)]
+pub fn animate_pieces(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut moving_query: Query<(Entity, &mut Transform, &mut PieceAnimation)>,
+    mut fade_out_query: Query<(Entity, &mut Sprite, &mut FadeOut), Without<FadeIn>>,
+    mut fade_in_query: Query<(Entity, &mut Sprite, &mut FadeIn), Without<FadeOut>>,
+    orientation: Res<BoardOrientation>,
+) {
+    let duration = animation_duration();
+    let easing = animation_easing();
+    let progress = |elapsed: Duration| elapsed.as_secs_f32() / duration.as_secs_f32().max(f32::EPSILON);
+
+    for (entity, mut transform, mut animation) in &mut moving_query {
+        animation.elapsed += time.delta();
+
+        if animation.elapsed >= duration {
+            finish_piece_animation(&mut commands, &mut transform, entity, &animation, *orientation);
+            continue;
+        }
+
+        let (x, y) = orientation.board_to_pixel_coords(animation.to.file, animation.to.rank);
+        let position = animation
+            .from
+            .lerp(Vec2::new(x, y), easing.apply(progress(animation.elapsed)));
+        transform.translation = position.extend(MOVING_PIECE_Z);
+    }
+
+    for (entity, mut sprite, mut fade) in &mut fade_out_query {
+        fade.elapsed += time.delta();
+
+        if fade.elapsed >= duration {
+            commands.entity(entity).despawn();
+        } else {
+            sprite.color.set_alpha(1. - easing.apply(progress(fade.elapsed)));
+        }
+    }
+
+    for (entity, mut sprite, mut fade) in &mut fade_in_query {
+        fade.elapsed += time.delta();
+
+        if fade.elapsed >= duration {
+            sprite.color.set_alpha(1.);
+            commands.entity(entity).remove::<FadeIn>();
+        } else {
+            sprite.color.set_alpha(easing.apply(progress(fade.elapsed)));
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+
+use chess_core::board::TilePos;
+
+use crate::display::{BoardOrientation, PIECE_SIZE};
+
+const ARROW_Z: f32 = 3.;
+/// Tile highlights are drawn above the last move highlight, but below the pieces
+const HIGHLIGHT_Z: f32 = 0.6;
+const ARROW_WIDTH: f32 = PIECE_SIZE * 0.15;
+const ARROW_HEAD_LENGTH: f32 = PIECE_SIZE * 0.4;
+/// Angle between the shaft and each side of the arrow head
+const ARROW_HEAD_ANGLE: f32 = PI / 5.;
+/// Rings are drawn above the pieces, so they aren't hidden by the piece on their tile
+const RING_Z: f32 = 2.5;
+const RING_OUTER_RADIUS: f32 = PIECE_SIZE * 0.48;
+const RING_WIDTH: f32 = PIECE_SIZE * 0.07;
+const DOT_Z: f32 = 2.;
+const DOT_RADIUS: f32 = PIECE_SIZE * 0.15;
+
+/// Spawn an arrow pointing from the centre of ``from`` to the centre of ``to``, returning the entity which all its parts are children of
+pub fn spawn_arrow<C: Component>(
+    commands: &mut Commands,
+    from: TilePos,
+    to: TilePos,
+    colour: Color,
+    marker: C,
+    orientation: BoardOrientation,
+) -> Entity {
+    let (from_x, from_y) = orientation.board_to_pixel_coords(from.file, from.rank);
+    let (to_x, to_y) = orientation.board_to_pixel_coords(to.file, to.rank);
+
+    let direction = Vec2::new(to_x - from_x, to_y - from_y);
+    let length = direction.length();
+
+    // The arrow is built along the x axis, then rotated to point at the target tile
+    let sprite = |length: f32| Sprite {
+        color: colour,
+        custom_size: Some(Vec2::new(length, ARROW_WIDTH)),
+        ..default()
+    };
+
+    commands
+        .spawn((
+            Transform::from_xyz(from_x, from_y, ARROW_Z).with_rotation(Quat::from_rotation_z(direction.to_angle())),
+            Visibility::default(),
+            marker,
+        ))
+        .with_children(|parent| {
+            // Stop the shaft short of the tip, so that it doesn't poke through the head
+            let shaft_length = ARROW_HEAD_LENGTH.mul_add(-ARROW_HEAD_ANGLE.cos(), length).max(0.);
+            parent.spawn((sprite(shaft_length), Transform::from_xyz(shaft_length / 2., 0., 0.)));
+
+            // Each side of the head points backwards from the tip
+            for side_angle in [PI - ARROW_HEAD_ANGLE, PI + ARROW_HEAD_ANGLE] {
+                let side_centre = Vec2::new(length, 0.) + Vec2::from_angle(side_angle) * ARROW_HEAD_LENGTH / 2.;
+
+                parent.spawn((
+                    sprite(ARROW_HEAD_LENGTH),
+                    Transform::from_xyz(side_centre.x, side_centre.y, 0.).with_rotation(Quat::from_rotation_z(side_angle)),
+                ));
+            }
+        })
+        .id()
+}
+
+/// Spawn a square highlight on ``tile``
+pub fn spawn_tile_highlight<C: Component>(
+    commands: &mut Commands,
+    tile: TilePos,
+    colour: Color,
+    marker: C,
+    orientation: BoardOrientation,
+) -> Entity {
+    let (x, y) = orientation.board_to_pixel_coords(tile.file, tile.rank);
+
+    commands
+        .spawn((
+            Sprite {
+                color: colour,
+                custom_size: Some(Vec2::splat(PIECE_SIZE)),
+                ..default()
+            },
+            Transform::from_xyz(x, y, HIGHLIGHT_Z),
+            marker,
+        ))
+        .id()
+}
+
+/// Spawn a ring around the edge of ``tile``
+pub fn spawn_tile_ring<C: Component>(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<ColorMaterial>,
+    tile: TilePos,
+    colour: Color,
+    marker: C,
+    orientation: BoardOrientation,
+) -> Entity {
+    let (x, y) = orientation.board_to_pixel_coords(tile.file, tile.rank);
+
+    commands
+        .spawn((
+            Mesh2d(meshes.add(Annulus::new(RING_OUTER_RADIUS - RING_WIDTH, RING_OUTER_RADIUS))),
+            MeshMaterial2d(materials.add(colour)),
+            Transform::from_xyz(x, y, RING_Z),
+            marker,
+        ))
+        .id()
+}
+
+/// Spawn a dot in the centre of ``tile``
+pub fn spawn_tile_dot<C: Component>(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<ColorMaterial>,
+    tile: TilePos,
+    colour: Color,
+    marker: C,
+    orientation: BoardOrientation,
+) -> Entity {
+    let (x, y) = orientation.board_to_pixel_coords(tile.file, tile.rank);
+
+    commands
+        .spawn((
+            Mesh2d(meshes.add(Circle::new(DOT_RADIUS))),
+            MeshMaterial2d(materials.add(colour)),
+            Transform::from_xyz(x, y, DOT_Z),
+            marker,
+        ))
+        .id()
+}
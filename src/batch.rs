@@ -0,0 +1,382 @@
+use std::{
+    collections::HashSet,
+    fs::{self, File, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use thiserror::Error;
+
+use chess_core::board::Player;
+
+use crate::{
+    board::player_to_move_at_ply,
+    book::{BookError, OpeningBook},
+    classification::MOVE_CLASSIFICATIONS,
+    eval_cache::{EvalCache, EvalCacheError},
+    eval_graph::EvalHistory,
+    pgn::{annotated_pgn, parse_pgn, PgnError},
+    uci::{UciEngine, UciError, ENGINE_COMMAND, REVIEW_DEPTH},
+    uci_info::UciEval,
+};
+
+const REPORT_FILE: &str = "report.csv";
+const PROGRESS_FILE: &str = "progress.txt";
+const DEFAULT_OUTPUT_DIR: &str = "analysis";
+
+/// Centipawn losses are capped so that a single missed mate doesn't dominate the average
+const MAX_CENTIPAWN_LOSS: i32 = 1000;
+
+#[derive(Error, Debug)]
+pub enum BatchError {
+    #[error("Usage: chess_gui analyse <pgn directory> [--out <directory>] [--depth <depth>]\n\t{0}")]
+    UsageError(String),
+
+    #[error("Batch analysis files could not be read/wrote to:\n\t{0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("Report {0} has different columns, so it can't be resumed (Use a different output directory):\n\t{1}")]
+    ReportHeaderError(PathBuf, String),
+
+    #[error(transparent)]
+    UciError(#[from] UciError),
+
+    #[error(transparent)]
+    PgnError(#[from] PgnError),
+
+    #[error(transparent)]
+    BookError(#[from] BookError),
+
+    #[error(transparent)]
+    EvalCacheError(#[from] EvalCacheError),
+}
+
+#[derive(Debug, Clone)]
+pub struct BatchOptions {
+    pub input_dir: PathBuf,
+    pub output_dir: PathBuf,
+    pub depth: u32,
+}
+
+impl BatchOptions {
+    /// # Errors
+    /// Returns an error if the arguments don't match the usage of the ``analyse`` subcommand
+    pub fn from_args(args: &[String]) -> Result<Self, BatchError> {
+        let mut input_dir = None;
+        let mut output_dir = PathBuf::from(DEFAULT_OUTPUT_DIR);
+        let mut depth = REVIEW_DEPTH;
+
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--out" => {
+                    output_dir = PathBuf::from(
+                        args.next()
+                            .ok_or_else(|| BatchError::UsageError("Missing --out value".into()))?,
+                    );
+                }
+                "--depth" => {
+                    depth = args
+                        .next()
+                        .and_then(|depth| depth.parse().ok())
+                        .ok_or_else(|| BatchError::UsageError("Missing or invalid --depth value".into()))?;
+                }
+                _ => input_dir = Some(PathBuf::from(arg)),
+            }
+        }
+
+        Ok(Self {
+            input_dir: input_dir.ok_or_else(|| BatchError::UsageError("Missing PGN directory".into()))?,
+            output_dir,
+            depth,
+        })
+    }
+}
+
+/// Accuracy, average centipawn loss, and classification counts for one player in one game
+#[derive(Debug, Clone, Default)]
+pub struct PlayerStats {
+    pub move_amt: usize,
+    pub total_centipawn_loss: i64,
+    pub total_accuracy: f32,
+    pub classification_counts: [usize; MOVE_CLASSIFICATIONS.len()],
+}
+
+impl PlayerStats {
+    #[must_use]
+    pub fn average_centipawn_loss(&self) -> f32 {
+        if self.move_amt == 0 {
+            0.
+        } else {
+            self.total_centipawn_loss as f32 / self.move_amt as f32
+        }
+    }
+
+    #[must_use]
+    pub fn accuracy(&self) -> f32 {
+        if self.move_amt == 0 {
+            100.
+        } else {
+            self.total_accuracy / self.move_amt as f32
+        }
+    }
+}
+
+/// Clamp an eval into centipawns, treating mates as the largest possible eval
+const fn eval_to_centipawns(eval: UciEval) -> i32 {
+    match eval {
+        UciEval::Centipawn(eval) => {
+            if eval > MAX_CENTIPAWN_LOSS {
+                MAX_CENTIPAWN_LOSS
+            } else if eval < -MAX_CENTIPAWN_LOSS {
+                -MAX_CENTIPAWN_LOSS
+            } else {
+                eval
+            }
+        }
+        UciEval::Mate(mate_in) => {
+            if mate_in > 0 {
+                MAX_CENTIPAWN_LOSS
+            } else {
+                -MAX_CENTIPAWN_LOSS
+            }
+        }
+    }
+}
+
+/// Calculate the stats of both players (White then Black) from a reviewed game
+#[must_use]
+pub fn game_stats(eval_history: &EvalHistory) -> [PlayerStats; 2] {
+    let mut stats = [PlayerStats::default(), PlayerStats::default()];
+
+    for ply in 1..eval_history.len() {
+        let (Some(eval_before), Some(eval_after)) = (eval_history.get_eval(ply - 1), eval_history.get_eval(ply)) else {
+            continue;
+        };
+
+        // The player who made this move, and the evals from their perspective
+        let player = player_to_move_at_ply(ply - 1);
+        let eval_before = eval_before.to_white_perspective(player);
+        let eval_after = eval_after.to_white_perspective(player);
+
+        let player_stats = &mut stats[player.to_index()];
+        player_stats.move_amt += 1;
+        player_stats.total_centipawn_loss += i64::from((eval_to_centipawns(eval_before) - eval_to_centipawns(eval_after)).max(0));
+
+        // Accuracy is based on how much of the player's winning chances were lost
+        let win_percent_lost = (eval_before.to_expected_score() - eval_after.to_expected_score()).max(0.) * 100.;
+        player_stats.total_accuracy += 103.166_8f32
+            .mul_add((-0.043_54 * win_percent_lost).exp(), -3.166_9)
+            .clamp(0., 100.);
+
+        if let Some(move_class) = eval_history.get_classification(ply) {
+            if let Some(i) = MOVE_CLASSIFICATIONS.iter().position(|&other| other == move_class) {
+                player_stats.classification_counts[i] += 1;
+            }
+        }
+    }
+
+    stats
+}
+
+/// Analyse every game in every PGN file in a directory, writing annotated PGNs and a report of each player's stats
+/// Games which were analysed by a previous (Interrupted) run are skipped
+/// # Errors
+/// Returns an error if the arguments are invalid
+/// Returns an error if the engine can't be started or communicated with
+/// Returns an error if the opening book exists, but can't be loaded
+/// Returns an error if the input directory can't be read, or the output files can't be written
+#[allow(clippy::too_many_lines)]
+pub fn run_batch_analysis(args: &[String]) -> Result<(), BatchError> {
+    let options = BatchOptions::from_args(args)?;
+
+    let mut pgn_paths = fs::read_dir(&options.input_dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension.eq_ignore_ascii_case("pgn"))
+        })
+        .collect::<Vec<_>>();
+    pgn_paths.sort();
+
+    fs::create_dir_all(&options.output_dir)?;
+
+    // Read which games were completed by a previous run
+    let progress_path = options.output_dir.join(PROGRESS_FILE);
+    let completed = fs::read_to_string(&progress_path)
+        .map(|progress| progress.lines().map(ToString::to_string).collect::<HashSet<_>>())
+        .unwrap_or_default();
+
+    let report_path = options.output_dir.join(REPORT_FILE);
+    let mut report = open_report(&report_path, &completed)?;
+    let mut progress = OpenOptions::new().create(true).append(true).open(&progress_path)?;
+
+    let mut engine = UciEngine::start(ENGINE_COMMAND)?;
+    let mut eval_cache = EvalCache::load()?;
+    let book = OpeningBook::load_configured()?;
+
+    for (file_i, pgn_path) in pgn_paths.iter().enumerate() {
+        let file_name = pgn_path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        // A file which can't be read is skipped, rather than stopping the rest of the run
+        let text = match fs::read_to_string(pgn_path) {
+            Ok(text) => text,
+            Err(e) => {
+                eprintln!(
+                    "[File {}/{}] {file_name}: Could not be read, skipping\n\t{e}",
+                    file_i + 1,
+                    pgn_paths.len()
+                );
+                continue;
+            }
+        };
+        let games = parse_pgn(&text);
+
+        for (game_i, game) in games.iter().enumerate() {
+            let progress_key = format!("{file_name}#{}", game_i + 1);
+            let prefix = format!(
+                "[File {}/{}, Game {}/{}] {progress_key}",
+                file_i + 1,
+                pgn_paths.len(),
+                game_i + 1,
+                games.len()
+            );
+
+            if completed.contains(&progress_key) {
+                println!("{prefix}: Already analysed, skipping");
+                continue;
+            }
+
+            let game = match game {
+                Ok(game) => game,
+                Err(e) => {
+                    eprintln!("{prefix}: Could not be parsed, skipping\n\t{e}");
+                    continue;
+                }
+            };
+
+            // Reviews are always played out from the starting position
+            if game.get_header("FEN").is_some() {
+                println!("{prefix}: Games from a custom position can't be analysed, skipping");
+                continue;
+            }
+
+            let eval_history = engine.review(&mut eval_cache, &game.moves, options.depth, book.as_ref(), |ply, _| {
+                print!("\r{prefix}: Ply {ply}/{}", game.moves.len());
+                std::io::stdout().flush().map_err(UciError::StdInOutError)
+            })?;
+
+            // Save the annotated game
+            let mut headers = game
+                .headers
+                .iter()
+                .filter(|(key, _)| key != "Annotator" && key != "AnnotatorDepth")
+                .cloned()
+                .collect::<Vec<_>>();
+            headers.push((String::from("Annotator"), ENGINE_COMMAND.to_string()));
+            headers.push((String::from("AnnotatorDepth"), options.depth.to_string()));
+
+            let annotated_path = annotated_file_path(&options.output_dir, pgn_path, game_i);
+            fs::write(&annotated_path, annotated_pgn(&game.moves, &eval_history, &headers)?)?;
+
+            // Add both players to the report
+            let stats = game_stats(&eval_history);
+            for player in [Player::White, Player::Black] {
+                let colour = format!("{player:?}");
+                let player_stats = &stats[player.to_index()];
+
+                writeln!(
+                    report,
+                    "{file_name},{},{colour},{},{:.1},{:.1},{}",
+                    game_i + 1,
+                    game.get_header(&colour).unwrap_or("?").replace(',', " "),
+                    player_stats.accuracy(),
+                    player_stats.average_centipawn_loss(),
+                    player_stats.classification_counts.map(|count| count.to_string()).join(",")
+                )?;
+            }
+
+            // Only mark the game as completed once all of its output has been written
+            writeln!(progress, "{progress_key}")?;
+
+            println!(
+                "\r{prefix}: Done (Accuracy {:.1} / {:.1}, ACPL {:.1} / {:.1})",
+                stats[0].accuracy(),
+                stats[1].accuracy(),
+                stats[0].average_centipawn_loss(),
+                stats[1].average_centipawn_loss()
+            );
+        }
+    }
+
+    engine.quit()?;
+
+    println!("Analysis complete, report saved to {}", report_path.display());
+
+    Ok(())
+}
+
+/// Open the report for appending, first removing the rows of any game which wasn't marked as completed
+/// # Errors
+/// Returns an error if the existing report's columns can't be converted to the current columns
+///
+/// A run interrupted after writing a game's rows, but before marking it as completed, analyses that game again
+fn open_report(report_path: &Path, completed: &HashSet<String>) -> Result<File, BatchError> {
+    let existing = fs::read_to_string(report_path).unwrap_or_default();
+
+    // Rows start with the file name and game number, which make up the game's progress key
+    let completed_prefixes = completed
+        .iter()
+        .filter_map(|key| key.rsplit_once('#'))
+        .map(|(file_name, game)| format!("{file_name},{game},"))
+        .collect::<Vec<_>>();
+
+    let header = format!(
+        "file,game,colour,player,accuracy,acpl,{}",
+        MOVE_CLASSIFICATIONS
+            .map(|move_class| format!("{move_class:?}").to_lowercase())
+            .join(",")
+    );
+
+    // Reports from before a classification was added are missing its column, which is filled with a count of 0
+    let mut lines = existing.lines();
+    let padding = match lines.next() {
+        None => String::new(),
+        Some(existing_header) if existing_header == header => String::new(),
+        Some(existing_header) if header.starts_with(&format!("{existing_header},")) => {
+            ",0".repeat(header.split(',').count() - existing_header.split(',').count())
+        }
+        Some(existing_header) => {
+            return Err(BatchError::ReportHeaderError(
+                report_path.to_path_buf(),
+                existing_header.to_string(),
+            ));
+        }
+    };
+
+    let mut contents = format!("{header}\n");
+    for row in lines.filter(|row| completed_prefixes.iter().any(|prefix| row.starts_with(prefix))) {
+        contents.push_str(row);
+        contents.push_str(&padding);
+        contents.push('\n');
+    }
+
+    // Replace the report in one step, so it isn't lost if this is interrupted
+    let temp_path = report_path.with_extension("csv.tmp");
+    fs::write(&temp_path, contents)?;
+    fs::rename(&temp_path, report_path)?;
+
+    Ok(OpenOptions::new().append(true).open(report_path)?)
+}
+
+fn annotated_file_path(output_dir: &Path, pgn_path: &Path, game_i: usize) -> PathBuf {
+    let stem = pgn_path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    output_dir.join(format!("{stem}_{}.pgn", game_i + 1))
+}
@@ -7,7 +7,8 @@ use chess_core::{
 
 use crate::{
     board::BoardBevy,
-    display::{board_to_pixel_coords, PIECE_SIZE},
+    display::{BoardOrientation, PIECE_SIZE},
+    theme::ThemeState,
 };
 
 #[derive(Event, Debug)]
@@ -39,7 +40,11 @@ pub fn bitboard_event_handler(
     board: Res<BoardBevy>,
     bitboard_entities: Query<Entity, With<BitBoardMarker>>,
     mut commands: Commands,
+    theme_state: Res<ThemeState>,
+    orientation: Res<BoardOrientation>,
 ) {
+    let overlay_colour = theme_state.theme().overlay.0;
+
     for ev in ev_display.read() {
         if ev.clear {
             for entity in bitboard_entities.iter() {
@@ -51,11 +56,11 @@ pub fn bitboard_event_handler(
             let bitboard = board.board[board_type];
 
             for pos in bitboard.to_tile_positions() {
-                let (x, y) = board_to_pixel_coords(pos.file, pos.rank);
+                let (x, y) = orientation.board_to_pixel_coords(pos.file, pos.rank);
 
                 commands.spawn((
                     Sprite {
-                        color: Color::linear_rgba(1., 0., 0., 0.75),
+                        color: overlay_colour,
                         ..default()
                     },
                     Transform::from_xyz(x, y, 2.).with_scale(Vec3::splat(PIECE_SIZE * 0.75)),
@@ -74,7 +79,7 @@ pub fn bitboard_event_handler(
 
                     let pos = TilePos::from_index(board.board.en_passant_tile.trailing_zeros());
 
-                    xy.push(board_to_pixel_coords(pos.file, pos.rank));
+                    xy.push(orientation.board_to_pixel_coords(pos.file, pos.rank));
                 }
                 2 | 3 => {
                     // Show attacked tiles
@@ -91,7 +96,7 @@ pub fn bitboard_event_handler(
                         .to_tile_positions()
                         .iter()
                         .map(|&pos| Into::<(u32, u32)>::into(pos))
-                        .map(|(i, j)| board_to_pixel_coords(i, j))
+                        .map(|(i, j)| orientation.board_to_pixel_coords(i, j))
                         .collect::<Vec<_>>();
 
                     xy.append(&mut attacked);
@@ -104,7 +109,7 @@ pub fn bitboard_event_handler(
             for (x, y) in xy {
                 commands.spawn((
                     Sprite {
-                        color: Color::linear_rgba(1., 0., 0., 0.75),
+                        color: overlay_colour,
                         ..default()
                     },
                     Transform::from_xyz(x, y, 2.).with_scale(Vec3::splat(PIECE_SIZE * 0.75)),
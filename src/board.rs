@@ -1,29 +1,121 @@
-use std::fmt;
+use std::{fmt, sync::OnceLock};
 
 use bevy::prelude::*;
 
 use chess_core::{
-    board::{Board, TilePos, BOARD_SIZE, PLAYERS},
+    board::{Board, Player, TilePos, BOARD_SIZE, DEFAULT_FEN, PLAYERS},
     move_history::HistoryMove,
     piece::Piece,
     piece_move::{PieceMove, PieceMoveType},
 };
 
 use crate::{
-    display::{get_piece_texture_atlas, translate_piece_entity, BackgroundColourEvent},
+    animation::{animate_piece_entity, fade_in_piece_entity, fade_out_piece_entity},
+    display::{get_piece_texture_atlas, BackgroundColourEvent, BoardOrientation},
     game_end::GameEndEvent,
     last_move::LastMoveEvent,
     piece::PieceBundle,
+    theme::Theme,
     uci::{transmit_to_uci, UciMessage, ENGINE_PLAYER},
     uci_event::{UciEvent, UciToBoardMessage},
 };
 
-#[derive(Resource, Clone, Default)]
+/// The position which games in the GUI start from, set by the ``--fen`` argument
+static START_POSITION: OnceLock<(String, Player)> = OnceLock::new();
+
+/// Start games from ``fen`` instead of the default position, this can only be set once
+/// # Errors
+/// Returns an error if the FEN can't be parsed, or if the start position has already been set
+pub fn set_start_fen(fen: &str) -> Result<(), String> {
+    let board = Board::from_fen(fen)?;
+
+    START_POSITION
+        .set((fen.to_string(), board.get_player()))
+        .map_err(|_| String::from("Start position has already been set"))
+}
+
+/// The FEN of the position which games start from
+#[must_use]
+pub fn start_fen() -> &'static str {
+    START_POSITION.get().map_or(DEFAULT_FEN, |(fen, _)| fen.as_str())
+}
+
+/// The board which games start from
+#[must_use]
+pub fn start_board() -> Board {
+    Board::from_fen(start_fen()).unwrap_or_default()
+}
+
+/// The player who is to move after ``ply`` moves have been played from the starting position
+#[must_use]
+pub fn player_to_move_at_ply(ply: usize) -> Player {
+    let start_player = START_POSITION.get().map_or(Player::White, |(_, player)| *player);
+
+    if ply.is_multiple_of(2) {
+        start_player
+    } else {
+        opponent(start_player)
+    }
+}
+
+#[must_use]
+pub const fn opponent(player: Player) -> Player {
+    match player {
+        Player::White => Player::Black,
+        Player::Black => Player::White,
+    }
+}
+
+/// Every legal move for the player who is to move
+#[must_use]
+pub fn get_all_possible_moves(board: &Board) -> Vec<PieceMove> {
+    let mut possible_moves = Vec::new();
+
+    for rank in 0..BOARD_SIZE {
+        for file in 0..BOARD_SIZE {
+            let pos = TilePos::new(file, rank);
+
+            if board.get_piece(pos).is_player(board.get_player()) {
+                possible_moves.append(&mut board.get_possible_moves(pos));
+            }
+        }
+    }
+
+    possible_moves
+}
+
+/// Check if the king of ``player`` is attacked by the opponent
+#[must_use]
+pub fn is_in_check(board: &Board, player: Player) -> bool {
+    checked_king(board, player).is_some()
+}
+
+/// The tile of ``player``'s king, if it is attacked by the opponent
+#[must_use]
+pub fn checked_king(board: &Board, player: Player) -> Option<TilePos> {
+    let attacked_tiles = board.get_attacked_tiles(opponent(player)).to_tile_positions();
+
+    board[Piece::get_player_piece(player, Piece::WKing)]
+        .to_tile_positions()
+        .into_iter()
+        .find(|king_pos| attacked_tiles.contains(king_pos))
+}
+
+#[derive(Resource, Clone)]
 pub struct BoardBevy {
     pub board: Board,
     pub entities: [[Option<Entity>; BOARD_SIZE as usize]; BOARD_SIZE as usize],
 }
 
+impl Default for BoardBevy {
+    fn default() -> Self {
+        Self {
+            board: start_board(),
+            entities: [[None; BOARD_SIZE as usize]; BOARD_SIZE as usize],
+        }
+    }
+}
+
 impl std::fmt::Display for BoardBevy {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> std::fmt::Result {
         writeln!(f, "Current Player: {:?}\n{}\n", self.board.player, self.board)
@@ -47,6 +139,7 @@ impl BoardBevy {
         background_ev: &mut EventWriter<BackgroundColourEvent>,
         game_end_ev: &mut EventWriter<GameEndEvent>,
         last_move_ev: &mut EventWriter<LastMoveEvent>,
+        orientation: BoardOrientation,
         piece_move: PieceMove,
     ) -> Option<()> {
         // Classify this move
@@ -99,7 +192,7 @@ impl BoardBevy {
         // Capture any pieces that should be captured
         if self.board.get_piece(piece_move.to) != Piece::None {
             if let Some(captured_entity) = self.get_entity(piece_move.to) {
-                commands.entity(captured_entity).despawn();
+                fade_out_piece_entity(commands, captured_entity);
             }
         }
 
@@ -119,7 +212,7 @@ impl BoardBevy {
                 let captured_entity = self
                     .get_entity(captured_piece_pos)
                     .expect("Could not get en passant capture entity");
-                commands.entity(captured_entity).despawn();
+                fade_out_piece_entity(commands, captured_entity);
             }
             PieceMoveType::Castling => {
                 // Rook was moved via castling
@@ -134,10 +227,12 @@ impl BoardBevy {
 
                 // TODO This is duplicated code
                 // Move the rook entity
-                translate_piece_entity(
+                animate_piece_entity(
+                    commands,
                     transform_query,
                     self.get_entity(rook_pos).expect("Rook entity was not at Rook pos"),
                     new_rook_pos,
+                    orientation,
                 );
             }
             PieceMoveType::Promotion(promoted_to) => {
@@ -157,7 +252,7 @@ impl BoardBevy {
         }
 
         if let Some(piece_entity) = self.get_entity(piece_move.from) {
-            translate_piece_entity(transform_query, piece_entity, piece_move.to);
+            animate_piece_entity(commands, transform_query, piece_entity, piece_move.to, orientation);
         }
 
         // Move the entity internally, after any translations or texture changes are applied
@@ -193,6 +288,8 @@ impl BoardBevy {
         background_ev: &mut EventWriter<BackgroundColourEvent>,
         last_move_ev: &mut EventWriter<LastMoveEvent>,
         uci_to_board_ev: &mut EventWriter<UciEvent>,
+        theme: &Theme,
+        orientation: BoardOrientation,
         history_move: HistoryMove,
     ) {
         self.board.undo_move(history_move);
@@ -229,7 +326,7 @@ impl BoardBevy {
         });
 
         // Move piece before spawning new entities, and also move entity translation
-        translate_piece_entity(transform_query, piece_entity, piece_move.from);
+        animate_piece_entity(commands, transform_query, piece_entity, piece_move.from, orientation);
         self.move_entity(piece_move.rev());
 
         match piece_move.move_type {
@@ -243,16 +340,19 @@ impl BoardBevy {
                         piece_move.to
                     };
 
-                    let (texture, texture_atlas_layout) = get_piece_texture_atlas(asset_server, texture_atlas_layouts);
+                    let (texture, texture_atlas_layout) = get_piece_texture_atlas(asset_server, texture_atlas_layouts, theme);
 
                     // Create new entity for the captured piece
-                    let captured_entity = PieceBundle::spawn(
+                    let mut captured_entity = PieceBundle::spawn(
                         commands,
                         captured_piece_tile.into(),
                         captured_piece,
                         texture,
                         texture_atlas_layout,
+                        theme.piece_scale(),
+                        orientation,
                     );
+                    fade_in_piece_entity(&mut captured_entity);
 
                     // Update the entities array to make it aware of the spawned piece
                     self.set_entity(captured_piece_tile, Some(captured_entity.id()));
@@ -266,19 +366,21 @@ impl BoardBevy {
                 // TODO This is duplicated code
                 let (rook_pos, new_rook_pos) = if kingside_castle {
                     (
-                        TilePos::new(BOARD_SIZE - 1, piece_move.from.rank),
+                        TilePos::new(5, piece_move.from.rank),
                         TilePos::new(BOARD_SIZE - 1, piece_move.from.rank),
                     )
                 } else {
-                    (TilePos::new(0, piece_move.from.rank), TilePos::new(0, piece_move.from.rank))
+                    (TilePos::new(3, piece_move.from.rank), TilePos::new(0, piece_move.from.rank))
                 };
 
                 // TODO This is duplicated code
                 // Move the rook entity
-                translate_piece_entity(
+                animate_piece_entity(
+                    commands,
                     transform_query,
                     self.get_entity(rook_pos).expect("Rook entity was not at Rook pos"),
                     new_rook_pos,
+                    orientation,
                 );
 
                 // TODO This is duplicated code
@@ -318,6 +420,15 @@ impl BoardBevy {
         last_move_ev.write(LastMoveEvent);
     }
 
+    /// The number of moves which have been played to reach the current position
+    #[must_use]
+    pub fn current_ply(&self) -> usize {
+        self.board
+            .move_history
+            .to_piece_move_string()
+            .map_or(0, |move_history| move_history.split_whitespace().count())
+    }
+
     pub fn move_piece_and_entity(&mut self, piece_move: PieceMove) {
         self.board.move_piece(piece_move);
 
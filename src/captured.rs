@@ -0,0 +1,240 @@
+use bevy::prelude::*;
+
+use chess_core::{
+    board::{Board, Player},
+    piece::Piece,
+    piece_move::PieceMoveType,
+};
+
+use crate::{
+    board::{opponent, BoardBevy},
+    display::{get_piece_texture_atlas, BoardOrientation, SidePanel, BOTTOM_UI_HEIGHT, TOP_UI_HEIGHT},
+    theme::ThemeState,
+};
+
+const PANEL_WIDTH_PX: f32 = 160.;
+const PANEL_COLOUR: Color = Color::linear_rgba(0.1, 0.1, 0.1, 0.85);
+const ICON_SIZE: Val = Val::Px(28.);
+const FONT_SIZE: f32 = 20.;
+
+/// The pieces each player has captured, and the material difference (Positive when White is ahead)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MaterialTally {
+    pub captured_by_white: Vec<Piece>,
+    pub captured_by_black: Vec<Piece>,
+    pub balance: i32,
+}
+
+impl MaterialTally {
+    /// Tally the captures and promotions in the move history, up to the move currently on the board
+    #[must_use]
+    pub fn from_board(board: &Board) -> Self {
+        let mut tally = Self::default();
+
+        // Walk a copy of the history backwards, so undone moves aren't counted
+        let mut history = board.move_history.clone();
+        while let Some(history_move) = history.traverse_prev() {
+            let (piece_move, captured_piece, _, _) = history_move.into();
+
+            if let Some((captured, player)) = captured_piece.and_then(|piece| piece.to_player().map(|player| (piece, player))) {
+                let capturer = opponent(player);
+                tally.balance += player_sign(capturer) * piece_value(captured);
+                tally.captured_mut(capturer).push(captured);
+            }
+
+            // A promoted pawn is worth the piece it became
+            if let PieceMoveType::Promotion(promoted_to) = piece_move.move_type {
+                if let Some(player) = promoted_to.to_player() {
+                    tally.balance += player_sign(player) * (piece_value(promoted_to) - piece_value(Piece::WPawn));
+                }
+            }
+        }
+
+        // Most valuable first
+        for captured in [&mut tally.captured_by_white, &mut tally.captured_by_black] {
+            captured.sort_by_key(|piece| -piece_value(*piece));
+        }
+
+        tally
+    }
+
+    #[must_use]
+    pub const fn captured(&self, player: Player) -> &Vec<Piece> {
+        match player {
+            Player::White => &self.captured_by_white,
+            Player::Black => &self.captured_by_black,
+        }
+    }
+
+    const fn captured_mut(&mut self, player: Player) -> &mut Vec<Piece> {
+        match player {
+            Player::White => &mut self.captured_by_white,
+            Player::Black => &mut self.captured_by_black,
+        }
+    }
+
+    /// How far ahead ``player`` is in material, such as "+3", or an empty string if they aren't ahead
+    #[must_use]
+    pub fn advantage_text(&self, player: Player) -> String {
+        let advantage = player_sign(player) * self.balance;
+
+        if advantage > 0 {
+            format!("+{advantage}")
+        } else {
+            String::new()
+        }
+    }
+}
+
+/// The material value of ``piece`` in pawns, kings aren't counted
+#[must_use]
+pub const fn piece_value(piece: Piece) -> i32 {
+    match piece {
+        Piece::WQueen | Piece::BQueen => 9,
+        Piece::WRook | Piece::BRook => 5,
+        Piece::WBishop | Piece::BBishop | Piece::WKnight | Piece::BKnight => 3,
+        Piece::WPawn | Piece::BPawn => 1,
+        Piece::WKing | Piece::BKing | Piece::None => 0,
+    }
+}
+
+const fn player_sign(player: Player) -> i32 {
+    match player {
+        Player::White => 1,
+        Player::Black => -1,
+    }
+}
+
+#[derive(Component)]
+pub struct CapturedPanel;
+
+/// The captures of the player shown at the top (Or bottom) of the board
+#[derive(Component)]
+pub struct CapturedTray {
+    pub top: bool,
+}
+
+/// A captured piece icon or material difference, rebuilt whenever the position changes
+#[derive(Component)]
+pub struct CapturedEntry;
+
+pub fn create_captured_panel(mut commands: Commands) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(TOP_UI_HEIGHT),
+                bottom: Val::Px(BOTTOM_UI_HEIGHT),
+                right: Val::Px(0.),
+                width: Val::Px(PANEL_WIDTH_PX),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::SpaceBetween,
+                padding: UiRect::all(Val::Px(8.)),
+                ..default()
+            },
+            BackgroundColor(PANEL_COLOUR),
+            CapturedPanel,
+            SidePanel,
+        ))
+        .with_children(|parent| {
+            for top in [true, false] {
+                parent.spawn((
+                    Node {
+                        flex_wrap: FlexWrap::Wrap,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    CapturedTray { top },
+                ));
+            }
+        });
+}
+
+/// Stack the tray beside any other panel shown on the right of the window, so the tray isn't covered by it
+#[allow(clippy::needless_pass_by_value)]
+#[allow(clippy::type_complexity)]
+pub fn position_captured_panel(
+    mut tray_query: Query<&mut Node, With<CapturedPanel>>,
+    panels: Query<(&Node, &Visibility), (With<SidePanel>, Without<CapturedPanel>)>,
+) {
+    let offset = panels
+        .iter()
+        .filter(|(node, visibility)| node.right == Val::Px(0.) && **visibility != Visibility::Hidden)
+        .filter_map(|(node, _)| match node.width {
+            Val::Px(width) => Some(width),
+            _ => None,
+        })
+        .fold(0., f32::max);
+
+    for mut node in &mut tray_query {
+        // Only move the tray when needed, as moving it lays out the board again
+        if node.right != Val::Px(offset) {
+            node.right = Val::Px(offset);
+        }
+    }
+}
+
+/// Show the pieces each player has captured next to their side of the board, with the material difference
+#[allow(clippy::needless_pass_by_value)]
+#[allow(clippy::too_many_arguments)]
+pub fn update_captured_panel(
+    mut commands: Commands,
+    board: Res<BoardBevy>,
+    theme_state: Res<ThemeState>,
+    asset_server: Res<AssetServer>,
+    mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+    tray_query: Query<(Entity, &CapturedTray)>,
+    entry_entities: Query<Entity, With<CapturedEntry>>,
+    orientation: Res<BoardOrientation>,
+) {
+    // Redraw when the board is flipped or the theme is changed too
+    if !board.is_changed() && !theme_state.is_changed() && !orientation.is_changed() {
+        return;
+    }
+
+    for entity in entry_entities.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    let tally = MaterialTally::from_board(&board.board);
+    let (texture, texture_atlas_layout) = get_piece_texture_atlas(&asset_server, &mut texture_atlas_layouts, theme_state.theme());
+
+    for (tray, CapturedTray { top }) in &tray_query {
+        // White is at the bottom unless the board is flipped
+        let player = if *top == orientation.flipped {
+            Player::White
+        } else {
+            Player::Black
+        };
+
+        commands.entity(tray).with_children(|parent| {
+            for piece in tally.captured(player) {
+                parent.spawn((
+                    ImageNode::from_atlas_image(
+                        texture.clone(),
+                        TextureAtlas {
+                            layout: texture_atlas_layout.clone(),
+                            index: piece.to_bitboard_index(),
+                        },
+                    ),
+                    Node {
+                        width: ICON_SIZE,
+                        height: ICON_SIZE,
+                        ..default()
+                    },
+                    CapturedEntry,
+                ));
+            }
+
+            parent.spawn((
+                Text::new(tally.advantage_text(player)),
+                TextFont {
+                    font_size: FONT_SIZE,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                CapturedEntry,
+            ));
+        });
+    }
+}
@@ -0,0 +1,51 @@
+use bevy::prelude::*;
+
+use crate::{
+    board::{checked_king, BoardBevy},
+    display::{BoardOrientation, PIECE_SIZE},
+};
+
+/// Layers of the glow, from the outer edge inwards, which stack to be brightest in the centre
+const CHECK_GLOW_RADII: [f32; 3] = [PIECE_SIZE * 0.5, PIECE_SIZE * 0.38, PIECE_SIZE * 0.26];
+const CHECK_GLOW_COLOUR: Color = Color::linear_rgba(1., 0., 0., 0.3);
+/// Depth of the glow, above the tile highlights but under the pieces
+const CHECK_GLOW_Z: f32 = 0.7;
+
+#[derive(Component)]
+pub struct CheckMarker;
+
+/// Put a red glow under the king of the player to move while it is in check
+#[allow(clippy::needless_pass_by_value)]
+pub fn update_check_highlight(
+    mut commands: Commands,
+    board: Res<BoardBevy>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    marker_entities: Query<Entity, With<CheckMarker>>,
+    orientation: Res<BoardOrientation>,
+) {
+    // Redraw after moves and undos, or when the board is flipped
+    if !board.is_changed() && !orientation.is_changed() {
+        return;
+    }
+
+    for entity in marker_entities.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    let Some(king_pos) = checked_king(&board.board, board.board.get_player()) else {
+        return;
+    };
+
+    let (x, y) = orientation.board_to_pixel_coords(king_pos.file, king_pos.rank);
+    let material = materials.add(CHECK_GLOW_COLOUR);
+
+    for (i, radius) in (0..).zip(CHECK_GLOW_RADII) {
+        commands.spawn((
+            Mesh2d(meshes.add(Circle::new(radius))),
+            MeshMaterial2d(material.clone()),
+            Transform::from_xyz(x, y, 0.01f32.mul_add(i as f32, CHECK_GLOW_Z)),
+            CheckMarker,
+        ));
+    }
+}
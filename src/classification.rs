@@ -1,12 +1,14 @@
 use bevy::prelude::*;
 
+use chess_core::board::TilePos;
+
 use crate::{
     board::BoardBevy,
-    display::{board_to_pixel_coords, get_classification_texture_atlas, CLASSIFICATION_SIZE_IMG, PIECE_SIZE},
+    display::{get_classification_texture_atlas, BoardOrientation, CLASSIFICATION_SIZE_IMG, PIECE_SIZE},
     uci_info::UciEval,
 };
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum MoveClassification {
     Best,
     Excellent,
@@ -15,8 +17,21 @@ pub enum MoveClassification {
     Mistake,
     Miss,
     Blunder,
+    /// A move from the opening book
+    Book,
 }
 
+pub const MOVE_CLASSIFICATIONS: [MoveClassification; 8] = [
+    MoveClassification::Best,
+    MoveClassification::Excellent,
+    MoveClassification::Good,
+    MoveClassification::Inaccuracy,
+    MoveClassification::Mistake,
+    MoveClassification::Miss,
+    MoveClassification::Blunder,
+    MoveClassification::Book,
+];
+
 impl MoveClassification {
     #[must_use]
     pub const fn to_atlas_index(&self) -> usize {
@@ -28,6 +43,38 @@ impl MoveClassification {
             Self::Mistake => 7,
             Self::Blunder => 8,
             Self::Miss => 9,
+            Self::Book => 5,
+        }
+    }
+
+    /// The Numeric Annotation Glyph used for this classification in PGN (``$2`` is "?", ``$4`` is "??", ``$6`` is "?!")
+    #[must_use]
+    pub const fn to_nag(&self) -> Option<u8> {
+        match self {
+            Self::Best | Self::Excellent | Self::Good | Self::Book => None,
+            Self::Inaccuracy => Some(6),
+            Self::Mistake | Self::Miss => Some(2),
+            Self::Blunder => Some(4),
+        }
+    }
+
+    /// Mistakes which should have the best line shown alongside them
+    #[must_use]
+    pub const fn is_mistake(&self) -> bool {
+        matches!(self, Self::Mistake | Self::Miss | Self::Blunder)
+    }
+
+    #[must_use]
+    pub const fn to_colour(&self) -> Color {
+        match self {
+            Self::Best => Color::linear_rgb(0.3, 0.8, 0.3),
+            Self::Excellent => Color::linear_rgb(0.4, 0.9, 0.5),
+            Self::Good => Color::linear_rgb(0.4, 0.7, 0.7),
+            Self::Inaccuracy => Color::linear_rgb(0.9, 0.8, 0.2),
+            Self::Mistake => Color::linear_rgb(0.9, 0.5, 0.1),
+            Self::Miss => Color::linear_rgb(0.9, 0.3, 0.3),
+            Self::Blunder => Color::linear_rgb(0.8, 0.1, 0.1),
+            Self::Book => Color::linear_rgb(0.65, 0.5, 0.35),
         }
     }
 }
@@ -35,6 +82,14 @@ impl MoveClassification {
 #[derive(Component)]
 pub struct MoveClassificationMarker;
 
+/// Where the classification badge is drawn for a move to ``tile`` (In the top right corner of the tile)
+#[must_use]
+pub fn classification_translation(tile: TilePos, orientation: BoardOrientation) -> Vec3 {
+    let (x, y) = orientation.board_to_pixel_coords(tile.file, tile.rank);
+
+    Vec3::new(x + PIECE_SIZE / 2.25, y + PIECE_SIZE / 2.25, 1.5)
+}
+
 #[must_use]
 pub const fn classify_move(evaluation_after: UciEval, evaluation_best: UciEval) -> MoveClassification {
     match (evaluation_after, evaluation_best) {
@@ -92,6 +147,7 @@ pub fn show_classification(
     asset_server: &Res<AssetServer>,
     texture_atlas_layouts: &mut ResMut<Assets<TextureAtlasLayout>>,
     move_class: MoveClassification,
+    orientation: BoardOrientation,
 ) -> Result<(), String> {
     // println!("Move Type: {move_class:?}\t\t{:?}\n", board.board.get_next_player());
 
@@ -102,8 +158,6 @@ pub fn show_classification(
     };
     let (last_move, _, _, _) = last_move.into();
 
-    let (x, y) = board_to_pixel_coords(last_move.to.file, last_move.to.rank);
-
     let (texture, texture_atlas_layout) = get_classification_texture_atlas(asset_server, texture_atlas_layouts);
 
     commands.spawn((
@@ -115,11 +169,8 @@ pub fn show_classification(
             }),
             ..default()
         },
-        Transform::from_scale(Vec3::splat((PIECE_SIZE * 0.4) / CLASSIFICATION_SIZE_IMG)).with_translation(Vec3::new(
-            x + PIECE_SIZE / 2.25,
-            y + PIECE_SIZE / 2.25,
-            1.5,
-        )),
+        Transform::from_scale(Vec3::splat((PIECE_SIZE * 0.4) / CLASSIFICATION_SIZE_IMG))
+            .with_translation(classification_translation(last_move.to, orientation)),
         MoveClassificationMarker,
     ));
 
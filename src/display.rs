@@ -1,39 +1,97 @@
-use bevy::prelude::*;
+use bevy::{prelude::*, sprite::Anchor, window::WindowResized};
 
 use chess_core::{
     board::{Player, TilePos, BOARD_SIZE},
     piece::{Piece, COLOUR_AMT, PIECE_AMT},
 };
 
-use crate::{board::BoardBevy, piece::PieceBundle};
+use crate::{
+    animation::{FadeOut, PieceAnimation},
+    bitboard_event::BitBoardMarker,
+    board::BoardBevy,
+    classification::{classification_translation, MoveClassificationMarker},
+    last_move::LastMoveMarker,
+    piece::PieceBundle,
+    possible_moves::PossibleMoveMarker,
+    theme::{Theme, ThemeState},
+    uci::ENGINE_PLAYER,
+};
 
 pub const PIECE_SIZE: f32 = 200.;
-pub const PIECE_SIZE_IMG: f32 = 150.;
 pub const BOARD_SPACING: f32 = 0.;
 
-pub const PIECE_TEXTURE_FILE: &str = "ChessPiecesArray.png";
+/// Height of the UI above the board (The eval bar, and the opening name below it)
+pub const TOP_UI_HEIGHT: f32 = 90.;
+/// Height of the UI below the board (The eval graph)
+pub const BOTTOM_UI_HEIGHT: f32 = 120.;
+const BOARD_MARGIN: f32 = 16.;
+const MIN_SQUARE_SIZE: f32 = 16.;
+
+const COORDINATE_FONT_SIZE: f32 = PIECE_SIZE * 0.18;
+/// Distance of the coordinate labels from the edge of their square
+const COORDINATE_PADDING: f32 = PIECE_SIZE * 0.05;
 
 pub const CLASSIFICATION_SIZE_IMG: f32 = 128.;
 pub const CLASSIFICATION_COLUMNS: usize = 6;
 pub const CLASSIFICATION_ROWS: usize = 4;
 pub const CLASSIFICATION_TEXTURE_FILE: &str = "MoveClassification.png";
 
-#[must_use]
-pub fn board_to_pixel_coords(file: u32, rank: u32) -> (f32, f32) {
-    (
-        (file as f32 - BOARD_SIZE as f32 / 2. + 0.5) * (PIECE_SIZE + BOARD_SPACING),
-        (rank as f32 - BOARD_SIZE as f32 / 2. + 0.5) * (PIECE_SIZE + BOARD_SPACING),
-    )
+/// Which way round the board is shown, which is flipped (Rank 0 at the top) when viewed from Black's side
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BoardOrientation {
+    pub flipped: bool,
 }
 
-#[must_use]
-pub fn pixel_to_board_coords(x: f32, y: f32) -> (u32, u32) {
-    (
-        ((((x / (PIECE_SIZE + BOARD_SPACING)) - 0.5 + BOARD_SIZE as f32 / 2.) as isize).unsigned_abs() as u32)
-            .clamp(0, BOARD_SIZE - 1),
-        ((((y / (PIECE_SIZE + BOARD_SPACING)) - 0.5 + BOARD_SIZE as f32 / 2.) as isize).unsigned_abs() as u32)
-            .clamp(0, BOARD_SIZE - 1),
-    )
+impl Default for BoardOrientation {
+    /// The board starts flipped when the human plays Black
+    fn default() -> Self {
+        Self {
+            flipped: matches!(ENGINE_PLAYER, Player::White),
+        }
+    }
+}
+
+impl BoardOrientation {
+    /// Convert between board coordinates and the coordinates shown on screen, which is the same mapping in both directions
+    #[must_use]
+    pub const fn orient(self, file: u32, rank: u32) -> (u32, u32) {
+        if self.flipped {
+            (BOARD_SIZE - 1 - file, BOARD_SIZE - 1 - rank)
+        } else {
+            (file, rank)
+        }
+    }
+
+    #[must_use]
+    pub fn board_to_pixel_coords(self, file: u32, rank: u32) -> (f32, f32) {
+        let (file, rank) = self.orient(file, rank);
+
+        (
+            (file as f32 - BOARD_SIZE as f32 / 2. + 0.5) * (PIECE_SIZE + BOARD_SPACING),
+            (rank as f32 - BOARD_SIZE as f32 / 2. + 0.5) * (PIECE_SIZE + BOARD_SPACING),
+        )
+    }
+
+    #[must_use]
+    pub fn pixel_to_board_coords(self, x: f32, y: f32) -> (u32, u32) {
+        self.orient(
+            ((((x / (PIECE_SIZE + BOARD_SPACING)) - 0.5 + BOARD_SIZE as f32 / 2.) as isize).unsigned_abs() as u32)
+                .clamp(0, BOARD_SIZE - 1),
+            ((((y / (PIECE_SIZE + BOARD_SPACING)) - 0.5 + BOARD_SIZE as f32 / 2.) as isize).unsigned_abs() as u32)
+                .clamp(0, BOARD_SIZE - 1),
+        )
+    }
+
+    /// The tile at a point in board space, or ``None`` if the point is off the board
+    #[must_use]
+    #[allow(clippy::cast_sign_loss)]
+    pub fn try_pixel_to_board_coords(self, x: f32, y: f32) -> Option<(u32, u32)> {
+        let file = (x / (PIECE_SIZE + BOARD_SPACING) + BOARD_SIZE as f32 / 2.).floor();
+        let rank = (y / (PIECE_SIZE + BOARD_SPACING) + BOARD_SIZE as f32 / 2.).floor();
+
+        let on_board = |i: f32| (0. ..BOARD_SIZE as f32).contains(&i);
+        (on_board(file) && on_board(rank)).then(|| self.orient(file as u32, rank as u32))
+    }
 }
 
 #[allow(clippy::needless_pass_by_value)]
@@ -42,30 +100,33 @@ pub fn display_board(
     asset_server: Res<AssetServer>,
     mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
     mut board: ResMut<BoardBevy>,
+    theme_state: Res<ThemeState>,
+    orientation: Res<BoardOrientation>,
 ) {
+    let theme = theme_state.theme();
+
     // Spawn Board Squares
     for rank in 0..BOARD_SIZE {
         for file in 0..BOARD_SIZE {
-            let (x, y) = board_to_pixel_coords(file, rank);
+            let (x, y) = orientation.board_to_pixel_coords(file, rank);
 
             // Create a board with alternating light and dark squares
             // Starting with a light square on A1 (Bottom Left for White)
             commands.spawn((
                 Sprite {
-                    color: if (file + rank) % 2 == 0 {
-                        Color::WHITE
-                    } else {
-                        Color::linear_rgb(0.4, 0.3, 0.8)
-                    },
+                    color: square_colour(theme, file, rank),
                     custom_size: Some(Vec2::new(PIECE_SIZE, PIECE_SIZE)),
                     ..default()
                 },
                 Transform::from_xyz(x, y, 0.),
+                BoardSquare { file, rank },
             ));
         }
     }
 
-    let (texture, texture_atlas_layout) = get_piece_texture_atlas(&asset_server, &mut texture_atlas_layouts);
+    spawn_coordinate_labels(&mut commands, theme, *orientation);
+
+    let (texture, texture_atlas_layout) = get_piece_texture_atlas(&asset_server, &mut texture_atlas_layouts, theme);
 
     // Spawn all the pieces where they are in the board.tiles array
     for rank in 0..BOARD_SIZE {
@@ -77,6 +138,8 @@ pub fn display_board(
                     board.board.get_piece(TilePos::new(file, rank)),
                     texture.clone(),
                     texture_atlas_layout.clone(),
+                    theme.piece_scale(),
+                    *orientation,
                 );
 
                 board.set_entity(TilePos::new(file, rank), Some(entity.id()));
@@ -85,16 +148,123 @@ pub fn display_board(
     }
 }
 
+#[must_use]
+pub const fn square_colour(theme: &Theme, file: u32, rank: u32) -> Color {
+    if (file + rank).is_multiple_of(2) {
+        theme.light_square.0
+    } else {
+        theme.dark_square.0
+    }
+}
+
+/// A square of the board, so it can be recoloured when the theme changes
+#[derive(Component)]
+pub struct BoardSquare {
+    pub file: u32,
+    pub rank: u32,
+}
+
+/// A coordinate label inside an edge square, which stays on the same square of the screen when the board is flipped
+#[derive(Component)]
+pub struct CoordinateLabel {
+    /// The column (For files) or row (For ranks) counted from the bottom left of the screen
+    pub slot: u32,
+    pub is_file: bool,
+}
+
+impl CoordinateLabel {
+    /// The text of this label for ``orientation`` (e.g. ``"a"`` or ``"8"``)
+    #[must_use]
+    pub fn text(&self, orientation: BoardOrientation) -> String {
+        let (file, rank) = orientation.orient(self.slot, self.slot);
+
+        if self.is_file {
+            char::from(b'a' + file as u8).to_string()
+        } else {
+            (rank + 1).to_string()
+        }
+    }
+}
+
+/// Label the files along the bottom row of squares, and the ranks along the left column
+fn spawn_coordinate_labels(commands: &mut Commands, theme: &Theme, orientation: BoardOrientation) {
+    for slot in 0..BOARD_SIZE {
+        for is_file in [true, false] {
+            // Orienting the screen position gives the board position which is drawn there
+            let (file, rank) = if is_file {
+                orientation.orient(slot, 0)
+            } else {
+                orientation.orient(0, slot)
+            };
+            let (x, y) = orientation.board_to_pixel_coords(file, rank);
+
+            let (anchor, translation) = if is_file {
+                (
+                    Anchor::BottomRight,
+                    Vec3::new(
+                        x + PIECE_SIZE / 2. - COORDINATE_PADDING,
+                        y - PIECE_SIZE / 2. + COORDINATE_PADDING,
+                        0.8,
+                    ),
+                )
+            } else {
+                (
+                    Anchor::TopLeft,
+                    Vec3::new(
+                        x - PIECE_SIZE / 2. + COORDINATE_PADDING,
+                        y + PIECE_SIZE / 2. - COORDINATE_PADDING,
+                        0.8,
+                    ),
+                )
+            };
+
+            let label = CoordinateLabel { slot, is_file };
+
+            // Use the colour of the neighbouring squares so the label stands out
+            let colour = square_colour(theme, file + 1, rank);
+
+            commands.spawn((
+                Text2d::new(label.text(orientation)),
+                TextFont {
+                    font_size: COORDINATE_FONT_SIZE,
+                    ..default()
+                },
+                TextColor(colour),
+                anchor,
+                Transform::from_translation(translation),
+                label,
+            ));
+        }
+    }
+}
+
+/// Relabel the coordinates when the board is flipped
+#[allow(clippy::needless_pass_by_value)]
+pub fn update_coordinate_labels(orientation: Res<BoardOrientation>, mut label_query: Query<(&mut Text2d, &CoordinateLabel)>) {
+    if !orientation.is_changed() {
+        return;
+    }
+
+    for (mut text, label) in &mut label_query {
+        let label_text = label.text(*orientation);
+
+        if text.0 != label_text {
+            text.0 = label_text;
+        }
+    }
+}
+
 #[allow(clippy::cast_sign_loss)]
 pub fn get_piece_texture_atlas(
     asset_server: &AssetServer,
     texture_atlas_layouts: &mut ResMut<Assets<TextureAtlasLayout>>,
+    theme: &Theme,
 ) -> (bevy::prelude::Handle<Image>, bevy::prelude::Handle<TextureAtlasLayout>) {
     // Texture atlas for all the pieces
     (
-        asset_server.load(PIECE_TEXTURE_FILE),
+        asset_server.load(&theme.piece_texture),
         texture_atlas_layouts.add(TextureAtlasLayout::from_grid(
-            UVec2::new(PIECE_SIZE_IMG as u32, PIECE_SIZE_IMG as u32),
+            UVec2::new(theme.piece_cell_size, theme.piece_cell_size),
             PIECE_AMT as u32,
             COLOUR_AMT as u32,
             None,
@@ -123,14 +293,167 @@ pub fn get_classification_texture_atlas(
 
 /// # Panics
 /// Panics if the transform query cannot find the entity at the specified position
-pub fn translate_piece_entity(transform_query: &mut Query<&mut Transform>, piece_entity: Entity, pos: TilePos) {
+pub fn translate_piece_entity(
+    transform_query: &mut Query<&mut Transform>,
+    piece_entity: Entity,
+    pos: TilePos,
+    orientation: BoardOrientation,
+) {
     let mut transform = transform_query
         .get_mut(piece_entity)
         .unwrap_or_else(|e| panic!("Could get piece entity at pos {pos}\n\t{e:?}"));
-    let (x, y) = board_to_pixel_coords(pos.file, pos.rank);
+    let (x, y) = orientation.board_to_pixel_coords(pos.file, pos.rank);
     transform.translation = Vec3::new(x, y, 1.);
 }
 
+/// The size of the board on screen
+///
+/// Board-space entities are laid out using ``PIECE_SIZE`` squares, and the camera is zoomed so that the board fits the window
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct BoardLayout {
+    /// Size of a square in logical pixels
+    pub square_size: f32,
+}
+
+impl Default for BoardLayout {
+    fn default() -> Self {
+        Self { square_size: PIECE_SIZE }
+    }
+}
+
+impl BoardLayout {
+    /// Convert a distance in logical pixels (Such as a mouse drag) into board-space units
+    #[must_use]
+    pub fn pixels_to_world(&self, pixels: Vec2) -> Vec2 {
+        pixels * PIECE_SIZE / self.square_size
+    }
+}
+
+/// A UI panel on the side of the window, which the board is shrunk to fit beside while it is visible
+#[derive(Component)]
+pub struct SidePanel;
+
+/// Fit the board into the space left by the UI whenever the window is resized or a side panel is shown, hidden, or moved
+///
+/// The camera's projection works in logical pixels, so the board is the same size on high DPI screens
+#[allow(clippy::needless_pass_by_value)]
+#[allow(clippy::type_complexity)]
+pub fn layout_board(
+    mut ev_window_resized: EventReader<WindowResized>,
+    mut layout: ResMut<BoardLayout>,
+    mut laid_out: Local<bool>,
+    windows: Query<&Window>,
+    panels: Query<(&Node, &Visibility), With<SidePanel>>,
+    changed_panels: Query<(), (With<SidePanel>, Or<(Changed<Visibility>, Changed<Node>)>)>,
+    mut camera_query: Query<(&mut Projection, &mut Transform), With<Camera2d>>,
+) {
+    if ev_window_resized.read().count() == 0 && changed_panels.is_empty() && *laid_out {
+        return;
+    }
+
+    let (Ok(window), Ok((mut projection, mut camera_transform))) = (windows.single(), camera_query.single_mut()) else {
+        return;
+    };
+    *laid_out = true;
+
+    // Leave space for any visible panels on either side
+    let (mut left, mut right) = (0_f32, 0_f32);
+    for (node, visibility) in &panels {
+        let Val::Px(width) = node.width else {
+            continue;
+        };
+
+        if *visibility == Visibility::Hidden {
+            continue;
+        }
+
+        // Panels can be stacked beside another panel, so their distance from the edge of the window is included
+        match (node.left, node.right) {
+            (Val::Px(offset), _) => left = left.max(offset + width),
+            (_, Val::Px(offset)) => right = right.max(offset + width),
+            _ => right = right.max(width),
+        }
+    }
+
+    let available_width = BOARD_MARGIN.mul_add(-2., window.width() - left - right);
+    let available_height = BOARD_MARGIN.mul_add(-2., window.height() - TOP_UI_HEIGHT - BOTTOM_UI_HEIGHT);
+
+    layout.square_size = (available_width.min(available_height) / BOARD_SIZE as f32).max(MIN_SQUARE_SIZE);
+    let world_per_pixel = PIECE_SIZE / layout.square_size;
+
+    if let Projection::Orthographic(orthographic) = projection.as_mut() {
+        orthographic.scale = world_per_pixel;
+    }
+
+    // Move the camera so the board (Centred on the origin) is drawn in the middle of the space left for it
+    let offset = Vec2::new(
+        left + BOARD_MARGIN + available_width / 2. - window.width() / 2.,
+        TOP_UI_HEIGHT + BOARD_MARGIN + available_height / 2. - window.height() / 2.,
+    );
+    camera_transform.translation.x = -offset.x * world_per_pixel;
+    camera_transform.translation.y = offset.y * world_per_pixel;
+}
+
+#[derive(Event)]
+pub struct BoardFlipEvent;
+
+/// Flip the board, moving every piece and marker to where its tile is now shown
+#[allow(clippy::needless_pass_by_value)]
+#[allow(clippy::type_complexity)]
+pub fn board_flip_event_handler(
+    mut ev_board_flip: EventReader<BoardFlipEvent>,
+    mut orientation: ResMut<BoardOrientation>,
+    board: Res<BoardBevy>,
+    mut transform_query: Query<&mut Transform>,
+    marker_entities: Query<
+        Entity,
+        Or<(
+            With<LastMoveMarker>,
+            With<PossibleMoveMarker>,
+            With<BitBoardMarker>,
+            With<FadeOut>,
+        )>,
+    >,
+    classification_entities: Query<Entity, With<MoveClassificationMarker>>,
+    mut animation_query: Query<&mut PieceAnimation>,
+) {
+    for _ in ev_board_flip.read() {
+        orientation.flipped = !orientation.flipped;
+
+        for rank in 0..BOARD_SIZE {
+            for file in 0..BOARD_SIZE {
+                if let Some(entity) = board.get_entity(TilePos::new(file, rank)) {
+                    translate_piece_entity(&mut transform_query, entity, TilePos::new(file, rank), *orientation);
+                }
+            }
+        }
+
+        // Moving pieces head for their flipped tile, so their start is mirrored too
+        for mut animation in &mut animation_query {
+            animation.from = -animation.from;
+        }
+
+        // Tile markers (And fading captured pieces) are centred on their tile, and the board is centred on the origin, so flipping the board mirrors them
+        for entity in marker_entities.iter() {
+            if let Ok(mut transform) = transform_query.get_mut(entity) {
+                transform.translation.x = -transform.translation.x;
+                transform.translation.y = -transform.translation.y;
+            }
+        }
+
+        // Classification badges sit in the corner of the tile the last move went to
+        if let Some(last_move) = board.board.move_history.get() {
+            let (last_move, _, _, _) = last_move.into();
+
+            for entity in classification_entities.iter() {
+                if let Ok(mut transform) = transform_query.get_mut(entity) {
+                    transform.translation = classification_translation(last_move.to, *orientation);
+                }
+            }
+        }
+    }
+}
+
 #[derive(Event)]
 pub struct BackgroundColourEvent {
     colour: Color,
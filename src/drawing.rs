@@ -0,0 +1,183 @@
+use bevy::prelude::*;
+
+use chess_core::board::TilePos;
+
+use crate::{
+    arrow::{spawn_arrow, spawn_tile_ring},
+    board::BoardBevy,
+    display::BoardOrientation,
+    eval_graph::EvalHistory,
+    premove::PremoveQueue,
+};
+
+/// The colours which can be drawn with, matching the letters used by ``[%cal]`` and ``[%csl]`` comments
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DrawingColour {
+    Green,
+    Red,
+    Yellow,
+    Blue,
+}
+
+impl DrawingColour {
+    /// Choose a colour from the held modifier keys (Green by default, Shift for red, Ctrl for yellow, and Alt for blue)
+    #[must_use]
+    pub fn from_modifiers(keys: &ButtonInput<KeyCode>) -> Self {
+        if keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+            Self::Red
+        } else if keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+            Self::Yellow
+        } else if keys.any_pressed([KeyCode::AltLeft, KeyCode::AltRight]) {
+            Self::Blue
+        } else {
+            Self::Green
+        }
+    }
+
+    #[must_use]
+    pub const fn to_letter(self) -> char {
+        match self {
+            Self::Green => 'G',
+            Self::Red => 'R',
+            Self::Yellow => 'Y',
+            Self::Blue => 'B',
+        }
+    }
+
+    #[must_use]
+    pub const fn colour(self) -> Color {
+        match self {
+            Self::Green => Color::linear_rgba(0.1, 0.6, 0.1, 0.8),
+            Self::Red => Color::linear_rgba(0.8, 0.1, 0.1, 0.8),
+            Self::Yellow => Color::linear_rgba(0.9, 0.7, 0.05, 0.8),
+            Self::Blue => Color::linear_rgba(0.1, 0.3, 0.9, 0.8),
+        }
+    }
+}
+
+/// An arrow or circle drawn on the board to annotate a position
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Drawing {
+    Circle {
+        tile: TilePos,
+        colour: DrawingColour,
+    },
+    Arrow {
+        from: TilePos,
+        to: TilePos,
+        colour: DrawingColour,
+    },
+}
+
+impl Drawing {
+    /// The tiles this drawing goes between, which are the same tile for circles
+    #[must_use]
+    pub const fn tiles(&self) -> (TilePos, TilePos) {
+        match *self {
+            Self::Circle { tile, .. } => (tile, tile),
+            Self::Arrow { from, to, .. } => (from, to),
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct DrawingMarker;
+
+/// Draw an arrow by dragging with the right mouse button, or circle a tile by right clicking it (Unless the click clears the premoves)
+#[allow(clippy::needless_pass_by_value)]
+#[allow(clippy::too_many_arguments)]
+pub fn drawing_input_handler(
+    mut ev_pressed: EventReader<Pointer<Pressed>>,
+    mut ev_released: EventReader<Pointer<Released>>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    board: Res<BoardBevy>,
+    mut eval_history: ResMut<EvalHistory>,
+    premove_queue: Res<PremoveQueue>,
+    orientation: Res<BoardOrientation>,
+    mut drawing_from: Local<Option<TilePos>>,
+) {
+    // Every hovered entity is sent the event, but only the tile matters
+    let event_tile = |position: Option<Vec3>| {
+        position
+            .and_then(|position| orientation.try_pixel_to_board_coords(position.x, position.y))
+            .map(TilePos::from)
+    };
+
+    if let Some(ev) = ev_pressed.read().filter(|ev| ev.button == PointerButton::Secondary).last() {
+        // A right click which clears the premoves doesn't draw as well (This runs before the premoves are cleared)
+        *drawing_from = if premove_queue.moves.is_empty() {
+            event_tile(ev.hit.position)
+        } else {
+            None
+        };
+    }
+
+    let released_tile = ev_released
+        .read()
+        .filter(|ev| ev.button == PointerButton::Secondary)
+        .last()
+        .and_then(|ev| event_tile(ev.hit.position));
+
+    if !mouse_buttons.just_released(MouseButton::Right) {
+        return;
+    }
+
+    // Releasing off the board doesn't draw anything
+    let (Some(from), Some(to)) = (drawing_from.take(), released_tile) else {
+        return;
+    };
+
+    let colour = DrawingColour::from_modifiers(&keys);
+    let drawing = if from == to {
+        Drawing::Circle { tile: from, colour }
+    } else {
+        Drawing::Arrow { from, to, colour }
+    };
+
+    eval_history.toggle_drawing(board.current_ply(), drawing);
+}
+
+/// Draw the arrows and circles of the position currently on the board
+#[allow(clippy::needless_pass_by_value)]
+pub fn update_drawings(
+    mut commands: Commands,
+    board: Res<BoardBevy>,
+    eval_history: Res<EvalHistory>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    drawing_entities: Query<Entity, With<DrawingMarker>>,
+    orientation: Res<BoardOrientation>,
+) {
+    // Redraw when the board is flipped too
+    if !board.is_changed() && !eval_history.is_changed() && !orientation.is_changed() {
+        return;
+    }
+
+    for entity in drawing_entities.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    let Some(record) = eval_history.get(board.current_ply()) else {
+        return;
+    };
+
+    for drawing in &record.drawings {
+        match *drawing {
+            Drawing::Circle { tile, colour } => {
+                spawn_tile_ring(
+                    &mut commands,
+                    &mut meshes,
+                    &mut materials,
+                    tile,
+                    colour.colour(),
+                    DrawingMarker,
+                    *orientation,
+                );
+            }
+            Drawing::Arrow { from, to, colour } => {
+                spawn_arrow(&mut commands, from, to, colour.colour(), DrawingMarker, *orientation);
+            }
+        }
+    }
+}
@@ -0,0 +1,199 @@
+use std::{collections::HashMap, fmt, fs, path::Path};
+
+use bevy::prelude::*;
+use thiserror::Error;
+
+use chess_core::board::Board;
+
+use crate::{
+    board::{start_board, BoardBevy},
+    display::TOP_UI_HEIGHT,
+    eval_bar::BAR_HEIGHT_PX,
+    eval_cache::fen_key,
+    pgn::{find_legal_move, parse_pgn, PgnError},
+};
+
+/// Opening lines in the same format as the lichess chess-openings TSVs (``eco``, ``name``, ``pgn``)
+pub const ECO_FILE: &str = "assets/eco.tsv";
+
+const LABEL_HEIGHT: Val = Val::Px(TOP_UI_HEIGHT - BAR_HEIGHT_PX);
+const LABEL_COLOUR: Color = Color::linear_rgba(0.1, 0.1, 0.1, 0.85);
+const FONT_SIZE: f32 = 24.;
+
+#[derive(Error, Debug)]
+pub enum EcoError {
+    #[error("ECO file could not be read:\n\t{0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("ECO line {0} could not be parsed:\n\t{1}")]
+    EcoParseError(usize, String),
+}
+
+/// A named opening, such as "C65 Ruy Lopez: Berlin Defense"
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EcoOpening {
+    pub eco: String,
+    pub name: String,
+}
+
+impl fmt::Display for EcoOpening {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.eco, self.name)
+    }
+}
+
+/// Every opening in the ECO file, keyed by the position at the end of its line so that transpositions are recognised
+#[derive(Resource, Debug, Clone)]
+pub struct EcoDatabase {
+    pub positions: HashMap<String, EcoOpening>,
+}
+
+impl Default for EcoDatabase {
+    fn default() -> Self {
+        Self::load(Path::new(ECO_FILE)).unwrap_or_else(|e| {
+            eprintln!("{e}");
+
+            Self {
+                positions: HashMap::new(),
+            }
+        })
+    }
+}
+
+impl EcoDatabase {
+    /// Load every opening in an ECO TSV file
+    /// # Errors
+    /// Returns an error if the file can't be read, or any line has missing fields or illegal moves
+    pub fn load(path: &Path) -> Result<Self, EcoError> {
+        let mut positions = HashMap::new();
+
+        // Skip the column names
+        for (i, line) in fs::read_to_string(path)?.lines().enumerate().skip(1) {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let parse_error = |e: String| EcoError::EcoParseError(i + 1, e);
+
+            let mut fields = line.split('\t');
+            let (Some(eco), Some(name), Some(pgn)) = (fields.next(), fields.next(), fields.next()) else {
+                return Err(parse_error(String::from("Expected eco, name, and pgn fields")));
+            };
+
+            let moves = parse_pgn(pgn)
+                .into_iter()
+                .next()
+                .transpose()
+                .map_err(|e| parse_error(e.to_string()))?
+                .map(|game| game.moves)
+                .unwrap_or_default();
+            let board = board_after_line(Board::default(), &moves).map_err(|e| parse_error(e.to_string()))?;
+
+            positions.insert(
+                fen_key(&board.to_fen()),
+                EcoOpening {
+                    eco: eco.to_string(),
+                    name: name.to_string(),
+                },
+            );
+        }
+
+        Ok(Self { positions })
+    }
+
+    /// The opening of the last position in ``moves`` (From the starting position) which is in the database
+    #[must_use]
+    pub fn find(&self, moves: &[String]) -> Option<&EcoOpening> {
+        let mut board = start_board();
+        let mut opening = self.positions.get(&fen_key(&board.to_fen()));
+
+        for algebraic in moves {
+            let Ok(piece_move) = find_legal_move(&board, algebraic) else {
+                break;
+            };
+            board.apply_move(piece_move);
+
+            if let Some(position_opening) = self.positions.get(&fen_key(&board.to_fen())) {
+                opening = Some(position_opening);
+            }
+        }
+
+        opening
+    }
+
+    /// PGN headers naming the opening which ``moves`` reached
+    #[must_use]
+    pub fn headers(&self, moves: &[String]) -> Vec<(String, String)> {
+        self.find(moves)
+            .map(|opening| {
+                vec![
+                    (String::from("ECO"), opening.eco.clone()),
+                    (String::from("Opening"), opening.name.clone()),
+                ]
+            })
+            .unwrap_or_default()
+    }
+}
+
+fn board_after_line(mut board: Board, moves: &[String]) -> Result<Board, PgnError> {
+    for algebraic in moves {
+        board.apply_move(find_legal_move(&board, algebraic)?);
+    }
+
+    Ok(board)
+}
+
+#[derive(Component)]
+pub struct OpeningLabel;
+
+pub fn create_opening_label(mut commands: Commands) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(BAR_HEIGHT_PX),
+                width: Val::Percent(100.),
+                height: LABEL_HEIGHT,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BackgroundColor(LABEL_COLOUR),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(""),
+                TextFont {
+                    font_size: FONT_SIZE,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                OpeningLabel,
+            ));
+        });
+}
+
+/// Show the opening of the current position, updating as moves are played or navigated
+#[allow(clippy::needless_pass_by_value)]
+pub fn update_opening_label(
+    board: Res<BoardBevy>,
+    eco_database: Res<EcoDatabase>,
+    mut label_query: Query<&mut Text, With<OpeningLabel>>,
+) {
+    if !board.is_changed() {
+        return;
+    }
+
+    let moves = board
+        .board
+        .move_history
+        .to_piece_move_string()
+        .map(|move_history| move_history.split_whitespace().map(ToString::to_string).collect::<Vec<_>>())
+        .unwrap_or_default();
+
+    let opening = eco_database.find(&moves).map(ToString::to_string).unwrap_or_default();
+
+    for mut text in &mut label_query {
+        text.0.clone_from(&opening);
+    }
+}
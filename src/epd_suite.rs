@@ -0,0 +1,358 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use thiserror::Error;
+
+use chess_core::board::Board;
+
+use crate::{
+    pgn::{find_legal_move, find_legal_move_from_san, to_san, PgnError},
+    uci::{UciEngine, UciError, ENGINE_COMMAND},
+    uci_info::uci_parse_info,
+};
+
+const DEFAULT_MOVETIME_MS: u64 = 1000;
+
+#[derive(Error, Debug)]
+pub enum EpdSuiteError {
+    #[error("Usage: chess_gui epd <epd file> [--engine <command>] [--movetime <milliseconds> | --depth <depth>]\n\t{0}")]
+    UsageError(String),
+
+    #[error("EPD file could not be read/wrote to:\n\t{0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error(transparent)]
+    UciError(#[from] UciError),
+
+    #[error(transparent)]
+    PgnError(#[from] PgnError),
+
+    #[error("EPD line {0} could not be parsed:\n\t{1}")]
+    EpdParseError(usize, String),
+}
+
+/// A test position, with its moves converted from SAN into long algebraic notation
+#[derive(Debug, Clone)]
+pub struct EpdPosition {
+    /// The line of the EPD file which this position was parsed from
+    pub epd: String,
+    pub fen: String,
+    pub id: Option<String>,
+    /// Moves which solve the position (``bm``)
+    pub best_moves: Vec<String>,
+    /// Moves which fail the position (``am``)
+    pub avoid_moves: Vec<String>,
+    /// Points given for each move (STS ``c0``, e.g. ``"Qe4=10, Qd3=5"``)
+    pub move_points: Vec<(String, u32)>,
+}
+
+impl EpdPosition {
+    /// Parse a line of an EPD file (The first four fields of a FEN, followed by operations ending in ``;``)
+    /// # Errors
+    /// Returns an error if the position can't be parsed
+    /// Returns an error if any of the moves in the operations aren't legal
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let fields = line.split_whitespace().take(4).collect::<Vec<_>>();
+        if fields.len() < 4 {
+            return Err(String::from("Missing position fields"));
+        }
+
+        let fen = format!("{} 0 1", fields.join(" "));
+        let board = Board::from_fen(&fen)?;
+
+        // Skip past the four position fields to find the operations
+        let mut operations = line.trim_start();
+        for _ in 0..4 {
+            operations = operations
+                .split_once(char::is_whitespace)
+                .map_or("", |(_, rest)| rest.trim_start());
+        }
+
+        let to_algebraic = |san: &str| {
+            find_legal_move_from_san(&board, san)
+                .map_err(|e| e.to_string())
+                .and_then(|piece_move| piece_move.to_algebraic())
+        };
+
+        let mut position = Self {
+            epd: line.trim().to_string(),
+            fen,
+            id: None,
+            best_moves: Vec::new(),
+            avoid_moves: Vec::new(),
+            move_points: Vec::new(),
+        };
+
+        for operation in operations.split(';') {
+            let Some((opcode, operands)) = operation.trim().split_once(char::is_whitespace) else {
+                continue;
+            };
+            let operands = operands.trim().trim_matches('"');
+
+            match opcode {
+                "id" => position.id = Some(operands.to_string()),
+                "bm" => position.best_moves = operands.split_whitespace().map(to_algebraic).collect::<Result<_, _>>()?,
+                "am" => position.avoid_moves = operands.split_whitespace().map(to_algebraic).collect::<Result<_, _>>()?,
+                "c0" if operands.contains('=') => {
+                    for move_points in operands.split(',') {
+                        let Some((san, points)) = move_points.trim().split_once('=') else {
+                            continue;
+                        };
+
+                        position.move_points.push((
+                            to_algebraic(san.trim())?,
+                            points.trim().parse().map_err(|_| format!("Invalid points \"{points}\""))?,
+                        ));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if position.best_moves.is_empty() && position.avoid_moves.is_empty() && position.move_points.is_empty() {
+            return Err(String::from("Position has no bm, am, or c0 operations"));
+        }
+
+        Ok(position)
+    }
+
+    /// The points an answer scores (The maximum points, or nothing, when there are no ``c0`` points)
+    #[must_use]
+    pub fn points(&self, algebraic: &str) -> u32 {
+        if self.move_points.is_empty() {
+            return u32::from(self.is_solution(algebraic));
+        }
+
+        self.move_points
+            .iter()
+            .filter(|(point_move, _)| point_move == algebraic)
+            .map(|(_, points)| *points)
+            .max()
+            .unwrap_or_default()
+    }
+
+    #[must_use]
+    pub fn max_points(&self) -> u32 {
+        self.move_points.iter().map(|(_, points)| *points).max().unwrap_or(1)
+    }
+
+    /// Whether this move solves the position
+    #[must_use]
+    pub fn is_solution(&self, algebraic: &str) -> bool {
+        let algebraic = algebraic.to_string();
+
+        if !self.best_moves.is_empty() || !self.avoid_moves.is_empty() {
+            (self.best_moves.is_empty() || self.best_moves.contains(&algebraic)) && !self.avoid_moves.contains(&algebraic)
+        } else {
+            self.points(&algebraic) == self.max_points()
+        }
+    }
+
+    /// Describe the expected answer in SAN (e.g. ``bm Qxh7+`` or ``am Nxe5``)
+    #[must_use]
+    pub fn describe_expected(&self) -> String {
+        let board = Board::from_fen(&self.fen).unwrap_or_default();
+        let to_san_line = |moves: &[String]| {
+            moves
+                .iter()
+                .filter_map(|algebraic| find_legal_move(&board, algebraic).ok())
+                .map(|piece_move| to_san(&board, piece_move))
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+
+        let mut expected = Vec::new();
+        if !self.best_moves.is_empty() {
+            expected.push(format!("bm {}", to_san_line(&self.best_moves)));
+        }
+        if !self.avoid_moves.is_empty() {
+            expected.push(format!("am {}", to_san_line(&self.avoid_moves)));
+        }
+        if self.best_moves.is_empty() && self.avoid_moves.is_empty() {
+            let best = self
+                .move_points
+                .iter()
+                .filter(|(_, points)| *points == self.max_points())
+                .map(|(algebraic, _)| algebraic.clone())
+                .collect::<Vec<_>>();
+            expected.push(format!("c0 {}", to_san_line(&best)));
+        }
+
+        expected.join(", ")
+    }
+}
+
+/// Load every position in an EPD file
+/// # Errors
+/// Returns an error if the file can't be read, or if any position can't be parsed
+pub fn load_epd_suite(path: &Path) -> Result<Vec<EpdPosition>, EpdSuiteError> {
+    fs::read_to_string(path)?
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+        .map(|(i, line)| EpdPosition::parse(line).map_err(|e| EpdSuiteError::EpdParseError(i + 1, e)))
+        .collect()
+}
+
+/// The engine's answer to a position
+#[derive(Debug, Clone)]
+pub struct EpdAnswer {
+    pub best_move: String,
+    pub points: u32,
+    pub solved: bool,
+    /// How long the engine took to find the answer it kept until the end of the search
+    pub time_to_solution: Option<Duration>,
+}
+
+/// Search a test position, keeping track of when the engine's best move last changed to a solution
+/// # Errors
+/// Returns an error if the engine can't be communicated with
+pub fn solve_position(engine: &mut UciEngine, position: &EpdPosition, go_command: &str) -> Result<EpdAnswer, EpdSuiteError> {
+    engine.new_game()?;
+
+    let search_start = Instant::now();
+    let mut solved_since = None;
+
+    let search = engine.go_watching(&format!("fen {}", position.fen), go_command, |line| {
+        if !line.starts_with("info") || !line.contains(" pv ") {
+            return;
+        }
+
+        let Some(pv_move) = uci_parse_info(line).ok().and_then(|info| info.pv.first().cloned()) else {
+            return;
+        };
+
+        if !position.is_solution(&pv_move) {
+            solved_since = None;
+        } else if solved_since.is_none() {
+            solved_since = Some(search_start.elapsed());
+        }
+    })?;
+
+    let solved = position.is_solution(&search.best_move);
+
+    Ok(EpdAnswer {
+        points: position.points(&search.best_move),
+        solved,
+        // The engine may have switched to a solution without reporting it in an info line
+        time_to_solution: solved.then(|| solved_since.unwrap_or_else(|| search_start.elapsed())),
+        best_move: search.best_move,
+    })
+}
+
+/// Run an EPD test suite against the engine, printing each answer and a summary of the results
+///
+/// The positions which weren't solved are saved to a new EPD file, and can be opened on the board with ``--fen``
+/// # Errors
+/// Returns an error if the arguments are invalid
+/// Returns an error if the suite can't be loaded, or the engine can't be communicated with
+/// Returns an error if the failures can't be saved
+pub fn run_epd_suite_cli(args: &[String]) -> Result<(), EpdSuiteError> {
+    let mut suite_path = None;
+    let mut engine_command = ENGINE_COMMAND.to_string();
+    let mut go_command = format!("go movetime {DEFAULT_MOVETIME_MS}");
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut next_number = || {
+            args.next()
+                .filter(|value| value.parse::<u64>().is_ok())
+                .ok_or_else(|| EpdSuiteError::UsageError(format!("Missing or invalid {arg} value")))
+        };
+
+        match arg.as_str() {
+            "--movetime" => go_command = format!("go movetime {}", next_number()?),
+            "--depth" => go_command = format!("go depth {}", next_number()?),
+            "--engine" => {
+                engine_command.clone_from(
+                    args.next()
+                        .ok_or_else(|| EpdSuiteError::UsageError(String::from("Missing --engine value")))?,
+                );
+            }
+            _ => suite_path = Some(PathBuf::from(arg)),
+        }
+    }
+
+    let suite_path = suite_path.ok_or_else(|| EpdSuiteError::UsageError(String::from("Missing EPD file")))?;
+    let positions = load_epd_suite(&suite_path)?;
+
+    let mut engine = UciEngine::start(&engine_command)?;
+
+    let mut solved_amt = 0;
+    let mut points = 0;
+    let mut max_points = 0;
+    let mut total_time_to_solution = Duration::ZERO;
+    let mut failures = Vec::new();
+
+    for (i, position) in positions.iter().enumerate() {
+        let answer = solve_position(&mut engine, position, &go_command)?;
+
+        let board = Board::from_fen(&position.fen).unwrap_or_default();
+        let answer_san = find_legal_move(&board, &answer.best_move)
+            .map_or_else(|_| answer.best_move.clone(), |piece_move| to_san(&board, piece_move));
+        let id = position.id.clone().unwrap_or_else(|| format!("#{}", i + 1));
+
+        points += answer.points;
+        max_points += position.max_points();
+
+        if let Some(time_to_solution) = answer.time_to_solution {
+            solved_amt += 1;
+            total_time_to_solution += time_to_solution;
+
+            println!(
+                "[{}/{}] {id}: {answer_san} Solved in {:.2}s ({}/{} points)",
+                i + 1,
+                positions.len(),
+                time_to_solution.as_secs_f32(),
+                answer.points,
+                position.max_points()
+            );
+        } else {
+            println!(
+                "[{}/{}] {id}: {answer_san} Failed, expected {} ({}/{} points)\n\tOpen on the board with: chess_gui --fen \"{}\"",
+                i + 1,
+                positions.len(),
+                position.describe_expected(),
+                answer.points,
+                position.max_points(),
+                position.fen
+            );
+
+            failures.push(position);
+        }
+    }
+
+    engine.quit()?;
+
+    println!(
+        "Solved {solved_amt}/{} positions, scoring {points}/{max_points} points",
+        positions.len()
+    );
+    if solved_amt > 0 {
+        println!(
+            "Average time to solution: {:.2}s",
+            total_time_to_solution.as_secs_f32() / solved_amt as f32
+        );
+    }
+
+    // Save the positions which weren't solved, so they can be looked at again
+    if !failures.is_empty() {
+        let failures_path = suite_path.with_file_name(format!(
+            "{}_failures.epd",
+            suite_path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_default()
+        ));
+
+        let failure_lines = failures.iter().map(|position| format!("{}\n", position.epd)).collect::<Vec<_>>();
+        fs::write(&failures_path, failure_lines.concat())?;
+
+        println!("Failed positions saved to {}", failures_path.display());
+    }
+
+    Ok(())
+}
@@ -17,7 +17,8 @@ pub struct EvalBarWhite;
 #[derive(Component)]
 pub struct EvalBarBlack;
 
-const BAR_HEIGHT: Val = Val::Px(50.0);
+pub const BAR_HEIGHT_PX: f32 = 50.0;
+const BAR_HEIGHT: Val = Val::Px(BAR_HEIGHT_PX);
 
 /// # Panics
 /// Panics if the move history can't be turned into a piece move string
@@ -75,19 +76,7 @@ pub fn create_eval_bar(mut commands: Commands, board: Res<BoardBevy>) {
 pub fn update_eval_bar(eval: Res<CurrentEval>, mut query: Query<(&mut Node, Option<&EvalBarWhite>, Option<&EvalBarBlack>)>) {
     // Only update the eval bar when the evaluation changes
     if eval.is_changed() {
-        let fraction = match eval.eval {
-            UciEval::Centipawn(cp) => {
-                let capped = cp.clamp(-1000, 1000); // cap extreme evals
-                0.5 + (capped as f32 / 2000.0) // between 0.0 and 1.0
-            }
-            UciEval::Mate(mate) => {
-                if mate > 0 {
-                    1.0 // White mates
-                } else {
-                    0.0 // Black mates
-                }
-            }
-        };
+        let fraction = eval_to_fraction(eval.eval);
 
         let white_percent = fraction * 100.0;
         let black_percent = 100.0 - white_percent;
@@ -102,3 +91,21 @@ pub fn update_eval_bar(eval: Res<CurrentEval>, mut query: Query<(&mut Node, Opti
         }
     }
 }
+
+/// Convert an evaluation into the fraction of the bar which should be white (Between 0.0 and 1.0)
+#[must_use]
+pub fn eval_to_fraction(eval: UciEval) -> f32 {
+    match eval {
+        UciEval::Centipawn(cp) => {
+            let capped = cp.clamp(-1000, 1000); // cap extreme evals
+            0.5 + (capped as f32 / 2000.0) // between 0.0 and 1.0
+        }
+        UciEval::Mate(mate) => {
+            if mate > 0 {
+                1.0 // White mates
+            } else {
+                0.0 // Black mates
+            }
+        }
+    }
+}
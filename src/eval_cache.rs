@@ -0,0 +1,162 @@
+use std::{
+    collections::HashMap,
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+};
+
+use thiserror::Error;
+
+use chess_core::{board::Board, piece_move::PieceMove};
+
+use crate::{
+    board::start_board,
+    uci::UciError,
+    uci_info::{UciEval, UciInfo},
+};
+
+/// File which searches are stored in so that reopened games can show their evals instantly (``None`` to only cache in memory)
+pub const EVAL_CACHE_FILE: Option<&str> = Some("eval_cache.tsv");
+
+#[derive(Error, Debug)]
+pub enum EvalCacheError {
+    #[error("Eval cache file could not be read:\n\t{0}")]
+    ReadError(std::io::Error),
+
+    #[error("Eval cache file could not be wrote to:\n\t{0}")]
+    WriteError(std::io::Error),
+}
+
+/// The deepest search that the engine has done for a position
+#[derive(Debug, Clone)]
+pub struct CachedSearch {
+    pub info: UciInfo,
+    pub best_move: String,
+}
+
+/// Searches keyed by the position they were performed on
+#[derive(Default, Debug)]
+pub struct EvalCache {
+    entries: HashMap<String, CachedSearch>,
+    file: Option<File>,
+}
+
+impl EvalCache {
+    /// Create a cache, reading any searches previously saved to ``EVAL_CACHE_FILE``
+    /// # Errors
+    /// Returns an error if the cache file exists but cannot be read, or cannot be opened for appending
+    pub fn load() -> Result<Self, EvalCacheError> {
+        let mut cache = Self::default();
+
+        let Some(path) = EVAL_CACHE_FILE else {
+            return Ok(cache);
+        };
+
+        if let Ok(file) = File::open(path) {
+            for line in BufReader::new(file).lines() {
+                // Skip any lines which are malformed
+                if let Some((key, search)) = parse_cache_line(&line.map_err(EvalCacheError::ReadError)?) {
+                    cache.insert_in_memory(key, search);
+                }
+            }
+        }
+
+        cache.file = Some(
+            OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .map_err(EvalCacheError::WriteError)?,
+        );
+
+        Ok(cache)
+    }
+
+    /// Get the search for this position, if it was searched to at least ``depth``
+    #[must_use]
+    pub fn get(&self, key: &str, depth: u32) -> Option<&CachedSearch> {
+        self.entries.get(key).filter(|search| search.info.depth >= depth)
+    }
+
+    /// Store this search, if it is deeper than the one already cached for this position
+    /// # Errors
+    /// Returns an error if the search cannot be appended to the cache file
+    pub fn insert(&mut self, key: String, search: CachedSearch) -> Result<(), EvalCacheError> {
+        // Searches which aren't deeper are ignored, so they aren't appended to the file either
+        if self.get(&key, search.info.depth).is_some() {
+            return Ok(());
+        }
+
+        if let Some(file) = self.file.as_mut() {
+            let (eval_type, eval_value) = match search.info.eval {
+                UciEval::Centipawn(eval) => ("cp", eval),
+                UciEval::Mate(mate_in) => ("mate", mate_in),
+            };
+
+            writeln!(
+                file,
+                "{key}\t{}\t{eval_type}\t{eval_value}\t{}\t{}",
+                search.info.depth,
+                search.best_move,
+                search.info.pv.join(" ")
+            )
+            .map_err(EvalCacheError::WriteError)?;
+        }
+
+        self.insert_in_memory(key, search);
+
+        Ok(())
+    }
+
+    fn insert_in_memory(&mut self, key: String, search: CachedSearch) {
+        if self.get(&key, search.info.depth).is_none() {
+            self.entries.insert(key, search);
+        }
+    }
+}
+
+fn parse_cache_line(line: &str) -> Option<(String, CachedSearch)> {
+    let mut fields = line.split('\t');
+
+    let key = fields.next()?.to_string();
+    let depth = fields.next()?.parse::<u32>().ok()?;
+    let eval = UciEval::new(fields.next()?, fields.next()?.parse::<i32>().ok()?);
+    let best_move = fields.next()?.to_string();
+    let pv = fields
+        .next()
+        .map(|pv| pv.split_whitespace().map(ToString::to_string).collect())
+        .unwrap_or_default();
+
+    Some((
+        key,
+        CachedSearch {
+            info: UciInfo { depth, eval, pv },
+            best_move,
+        },
+    ))
+}
+
+/// Replay ``move_history`` from the starting position
+/// # Errors
+/// Returns an error if any of the moves cannot be parsed
+pub fn board_after_moves(move_history: &str) -> Result<Board, UciError> {
+    let mut board = start_board();
+
+    for algebraic in move_history.split_whitespace() {
+        board.apply_move(PieceMove::from_algebraic(algebraic).map_err(UciError::PieceMoveParseError)?);
+    }
+
+    Ok(board)
+}
+
+/// Key the position by its FEN without the move clocks, so that transpositions share the same entry
+/// # Errors
+/// Returns an error if any of the moves cannot be parsed
+pub fn position_key(move_history: &str) -> Result<String, UciError> {
+    Ok(fen_key(&board_after_moves(move_history)?.to_fen()))
+}
+
+/// Key a FEN by its first four fields, dropping the move clocks
+#[must_use]
+pub fn fen_key(fen: &str) -> String {
+    fen.split_whitespace().take(4).collect::<Vec<_>>().join(" ")
+}
@@ -0,0 +1,217 @@
+use bevy::prelude::*;
+
+use crate::{
+    board::BoardBevy, classification::MoveClassification, display::BOTTOM_UI_HEIGHT, drawing::Drawing,
+    eval_bar::eval_to_fraction, last_move::LastMoveEvent, move_history::TargetPly, uci_info::UciEval,
+};
+
+const GRAPH_HEIGHT: Val = Val::Px(BOTTOM_UI_HEIGHT);
+const GRAPH_BACKGROUND_COLOUR: Color = Color::linear_rgb(0., 0., 0.);
+const GRAPH_CURRENT_PLY_COLOUR: Color = Color::linear_rgb(0.3, 0.3, 0.3);
+const GRAPH_MARKER_HEIGHT: Val = Val::Px(6.0);
+
+/// Everything known about the position after a ply
+#[derive(Default, Clone, Debug)]
+pub struct PlyRecord {
+    /// Evaluation of this position, from White's perspective
+    pub eval: Option<UciEval>,
+    /// Classification of the move which was played to reach this position
+    pub classification: Option<MoveClassification>,
+    /// The move which was played to reach this position (In long algebraic notation)
+    pub played_move: Option<String>,
+    /// The engine's principal variation from this position
+    pub best_line: Vec<String>,
+    /// Arrows and circles drawn on this position
+    pub drawings: Vec<Drawing>,
+}
+
+/// Records for every ply of the game
+#[derive(Resource, Default, Clone, Debug)]
+pub struct EvalHistory {
+    pub plies: Vec<PlyRecord>,
+}
+
+impl EvalHistory {
+    fn get_mut_or_insert(&mut self, ply: usize) -> &mut PlyRecord {
+        if self.plies.len() <= ply {
+            self.plies.resize(ply + 1, PlyRecord::default());
+        }
+
+        &mut self.plies[ply]
+    }
+
+    pub fn set_eval(&mut self, ply: usize, eval: UciEval) {
+        self.get_mut_or_insert(ply).eval = Some(eval);
+    }
+
+    pub fn set_classification(&mut self, ply: usize, move_class: MoveClassification) {
+        self.get_mut_or_insert(ply).classification = Some(move_class);
+    }
+
+    pub fn set_played_move(&mut self, ply: usize, played_move: String) {
+        self.get_mut_or_insert(ply).played_move = Some(played_move);
+    }
+
+    pub fn set_best_line(&mut self, ply: usize, best_line: Vec<String>) {
+        self.get_mut_or_insert(ply).best_line = best_line;
+    }
+
+    /// Add ``drawing`` to this position, replacing the colour of a drawing between the same tiles, or removing it if it is identical
+    pub fn toggle_drawing(&mut self, ply: usize, drawing: Drawing) {
+        let drawings = &mut self.get_mut_or_insert(ply).drawings;
+
+        match drawings.iter().position(|other| other.tiles() == drawing.tiles()) {
+            Some(i) if drawings[i] == drawing => {
+                drawings.remove(i);
+            }
+            Some(i) => drawings[i] = drawing,
+            None => drawings.push(drawing),
+        }
+    }
+
+    #[must_use]
+    pub fn get(&self, ply: usize) -> Option<&PlyRecord> {
+        self.plies.get(ply)
+    }
+
+    #[must_use]
+    pub fn get_eval(&self, ply: usize) -> Option<UciEval> {
+        self.get(ply).and_then(|record| record.eval)
+    }
+
+    #[must_use]
+    pub fn get_classification(&self, ply: usize) -> Option<MoveClassification> {
+        self.get(ply).and_then(|record| record.classification)
+    }
+
+    /// Remove every record from ``ply`` onwards
+    pub fn truncate(&mut self, ply: usize) {
+        self.plies.truncate(ply);
+    }
+
+    /// The number of plies which have any information stored
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.plies.len()
+    }
+
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.plies.is_empty()
+    }
+}
+
+#[derive(Component)]
+pub struct EvalGraph;
+
+#[derive(Component)]
+pub struct EvalGraphColumn {
+    pub ply: usize,
+}
+
+pub fn create_eval_graph(mut commands: Commands) {
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(0.),
+            width: Val::Percent(100.),
+            height: GRAPH_HEIGHT,
+            flex_direction: FlexDirection::Row,
+            align_items: AlignItems::Stretch,
+            ..default()
+        },
+        BackgroundColor(GRAPH_BACKGROUND_COLOUR),
+        EvalGraph,
+    ));
+}
+
+/// Rebuild the graph whenever an eval is stored or the board moves to another ply
+#[allow(clippy::needless_pass_by_value)]
+pub fn update_eval_graph(
+    mut commands: Commands,
+    eval_history: Res<EvalHistory>,
+    board: Res<BoardBevy>,
+    mut ev_last_move: EventReader<LastMoveEvent>,
+    graph_query: Query<Entity, With<EvalGraph>>,
+    column_entities: Query<Entity, With<EvalGraphColumn>>,
+) {
+    if !eval_history.is_changed() && ev_last_move.read().count() == 0 {
+        return;
+    }
+
+    let Ok(graph) = graph_query.single() else {
+        return;
+    };
+
+    for entity in column_entities.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    let current_ply = board.current_ply();
+    let ply_amt = eval_history.len().max(current_ply + 1);
+
+    commands.entity(graph).with_children(|parent| {
+        // Plies without an eval continue the line from the previous ply
+        let mut fraction = 0.5;
+
+        for ply in 0..ply_amt {
+            if let Some(eval) = eval_history.get_eval(ply) {
+                fraction = eval_to_fraction(eval);
+            }
+
+            parent
+                .spawn((
+                    Button,
+                    Node {
+                        flex_grow: 1.,
+                        height: Val::Percent(100.),
+                        flex_direction: FlexDirection::Column,
+                        justify_content: JustifyContent::FlexEnd,
+                        ..default()
+                    },
+                    BackgroundColor(if ply == current_ply {
+                        GRAPH_CURRENT_PLY_COLOUR
+                    } else {
+                        GRAPH_BACKGROUND_COLOUR
+                    }),
+                    EvalGraphColumn { ply },
+                ))
+                .with_children(|column| {
+                    // Mark the classification of the move which led to this ply on top of the curve
+                    if let Some(move_class) = eval_history.get_classification(ply) {
+                        column.spawn((
+                            Node {
+                                width: Val::Percent(100.),
+                                height: GRAPH_MARKER_HEIGHT,
+                                ..default()
+                            },
+                            BackgroundColor(move_class.to_colour()),
+                        ));
+                    }
+
+                    // White area below the curve
+                    column.spawn((
+                        Node {
+                            width: Val::Percent(100.),
+                            height: Val::Percent(fraction * 100.),
+                            ..default()
+                        },
+                        BackgroundColor(Color::WHITE),
+                    ));
+                });
+        }
+    });
+}
+
+/// Jump the board to the ply of any column which was clicked
+#[allow(clippy::needless_pass_by_value)]
+pub fn eval_graph_interaction_handler(
+    interaction_query: Query<(&Interaction, &EvalGraphColumn), Changed<Interaction>>,
+    mut target_ply: ResMut<TargetPly>,
+) {
+    for (interaction, column) in &interaction_query {
+        if *interaction == Interaction::Pressed {
+            target_ply.jump_to(column.ply);
+        }
+    }
+}
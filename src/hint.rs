@@ -0,0 +1,194 @@
+use bevy::prelude::*;
+
+use chess_core::{board::Player, piece_move::PieceMove};
+
+use crate::{
+    arrow::{spawn_arrow, spawn_tile_highlight},
+    board::BoardBevy,
+    display::{BoardOrientation, TOP_UI_HEIGHT},
+    eval_bar::BAR_HEIGHT_PX,
+    uci::{transmit_to_uci, UciMessage},
+};
+
+/// Depth used when searching for a hint
+pub const HINT_DEPTH: u32 = 16;
+
+const HINT_COLOUR: Color = Color::linear_rgba(0.1, 0.8, 0.2, 0.8);
+const LABEL_FONT_SIZE: f32 = 18.;
+
+/// The engine's suggested move for the position after ``move_history``
+#[derive(Debug, Clone)]
+pub struct Hint {
+    pub move_history: String,
+    /// ``None`` if the player to move has no moves
+    pub piece_move: Option<PieceMove>,
+    /// When false, only the piece to move is shown
+    pub fully_revealed: bool,
+}
+
+#[derive(Resource, Debug, Clone, Default)]
+pub struct HintState {
+    /// Show only the piece to move, then the full move when a hint is asked for again
+    pub reveal_piece_first: bool,
+    /// Amount of hints used by each player, indexed by ``Player::to_index``
+    pub hints_used: [usize; 2],
+    pub hint: Option<Hint>,
+    /// The move history of the position which the engine is currently finding a hint for
+    pub pending: Option<String>,
+}
+
+impl HintState {
+    /// PGN headers recording how many hints each player used
+    #[must_use]
+    pub fn headers(&self) -> Vec<(String, String)> {
+        [Player::White, Player::Black]
+            .into_iter()
+            .map(|player| (format!("{player:?}Hints"), self.hints_used[player.to_index()].to_string()))
+            .collect()
+    }
+}
+
+#[derive(Event)]
+pub enum HintEvent {
+    Request,
+    ToggleRevealPieceFirst,
+}
+
+#[derive(Component)]
+pub struct HintMarker;
+
+/// Shows how hints are revealed, on the left of the opening label
+#[derive(Component)]
+pub struct HintModeLabel;
+
+/// # Panics
+/// Panics if the move history can't be converted to a string to send to via uci to the engine
+/// Panics if message cannot be sent via uci
+#[allow(clippy::needless_pass_by_value)]
+pub fn hint_event_handler(mut ev_hint: EventReader<HintEvent>, mut hint_state: ResMut<HintState>, board: Res<BoardBevy>) {
+    for ev in ev_hint.read() {
+        match ev {
+            HintEvent::Request => {
+                // There is no move to hint at once the game has ended
+                if board.board.has_game_ended().is_some() {
+                    continue;
+                }
+
+                let move_history = board
+                    .board
+                    .move_history
+                    .to_piece_move_string()
+                    .expect("Could not convert move history into piece move string");
+
+                // Asking again for a partially revealed hint reveals the rest of the move
+                if let Some(hint) = hint_state.hint.as_mut() {
+                    if hint.move_history == move_history {
+                        hint.fully_revealed = true;
+                        continue;
+                    }
+                }
+
+                // Don't count the same hint twice while the engine is still searching
+                if hint_state.pending.as_ref() == Some(&move_history) {
+                    continue;
+                }
+
+                hint_state.hints_used[board.board.get_player().to_index()] += 1;
+                hint_state.pending = Some(move_history.clone());
+
+                transmit_to_uci(UciMessage::Hint { move_history }).unwrap_or_else(|e| panic!("{e}"));
+            }
+            HintEvent::ToggleRevealPieceFirst => {
+                hint_state.reveal_piece_first = !hint_state.reveal_piece_first;
+            }
+        }
+    }
+}
+
+/// Draw the hint for the position currently on the board, as an arrow or a highlight of the piece to move
+#[allow(clippy::needless_pass_by_value)]
+pub fn update_hint_display(
+    mut commands: Commands,
+    hint_state: Res<HintState>,
+    board: Res<BoardBevy>,
+    hint_entities: Query<Entity, With<HintMarker>>,
+    orientation: Res<BoardOrientation>,
+) {
+    // Redraw when the board is flipped too
+    if !hint_state.is_changed() && !board.is_changed() && !orientation.is_changed() {
+        return;
+    }
+
+    for entity in hint_entities.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    let Some((hint, piece_move)) = hint_state
+        .hint
+        .as_ref()
+        .and_then(|hint| hint.piece_move.map(|piece_move| (hint, piece_move)))
+    else {
+        return;
+    };
+
+    // Hints are only shown for the position they were found for
+    if board.board.move_history.to_piece_move_string().ok().as_ref() != Some(&hint.move_history) {
+        return;
+    }
+
+    if hint.fully_revealed {
+        spawn_arrow(
+            &mut commands,
+            piece_move.from,
+            piece_move.to,
+            HINT_COLOUR,
+            HintMarker,
+            *orientation,
+        );
+    } else {
+        spawn_tile_highlight(&mut commands, piece_move.from, HINT_COLOUR, HintMarker, *orientation);
+    }
+}
+
+pub fn create_hint_mode_label(mut commands: Commands) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(BAR_HEIGHT_PX),
+                left: Val::Px(8.),
+                height: Val::Px(TOP_UI_HEIGHT - BAR_HEIGHT_PX),
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            // Drawn over the opening label, which spans the width of the window
+            ZIndex(1),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(""),
+                TextFont {
+                    font_size: LABEL_FONT_SIZE,
+                    ..default()
+                },
+                TextColor(HINT_COLOUR),
+                HintModeLabel,
+            ));
+        });
+}
+
+/// Show whether hints reveal the piece to move first, or the whole move at once
+#[allow(clippy::needless_pass_by_value)]
+pub fn update_hint_mode_label(hint_state: Res<HintState>, mut label_query: Query<&mut Text, With<HintModeLabel>>) {
+    if !hint_state.is_changed() {
+        return;
+    }
+
+    for mut text in &mut label_query {
+        text.0 = String::from(if hint_state.reveal_piece_first {
+            "Hints: Piece first"
+        } else {
+            "Hints: Whole move"
+        });
+    }
+}
@@ -0,0 +1,191 @@
+use bevy::prelude::*;
+
+use crate::{
+    board::{start_board, start_fen},
+    display::{SidePanel, TOP_UI_HEIGHT},
+    eval_graph::EvalHistory,
+    move_history::TargetPly,
+    pgn::{fen_start_ply, move_number},
+    uci_info::UciEval,
+};
+
+const KEY_MOMENT_AMT: usize = 5;
+/// Swings in expected score smaller than this aren't turning points
+const KEY_MOMENT_MIN_SWING: f32 = 0.05;
+
+const PANEL_WIDTH: Val = Val::Px(420.0);
+const PANEL_TOP: Val = Val::Px(TOP_UI_HEIGHT);
+const PANEL_COLOUR: Color = Color::linear_rgba(0.1, 0.1, 0.1, 0.85);
+const ENTRY_COLOUR: Color = Color::linear_rgb(0.2, 0.2, 0.2);
+const FONT_SIZE: f32 = 20.;
+
+/// A ply where the expected score changed by a large amount
+#[derive(Debug, Clone)]
+pub struct KeyMoment {
+    pub ply: usize,
+    pub played_move: String,
+    pub best_move: Option<String>,
+    pub eval_before: UciEval,
+    pub eval_after: UciEval,
+    pub swing: f32,
+}
+
+impl KeyMoment {
+    #[must_use]
+    pub fn describe(&self) -> String {
+        // Count from the starting position's move number, which may have Black to move
+        let start_ply = fen_start_ply(start_fen(), &start_board());
+        let move_number = move_number(start_ply + self.ply, true).unwrap_or_default();
+
+        let best_move = match &self.best_move {
+            Some(best_move) if *best_move != self.played_move => format!("(Best: {best_move})"),
+            _ => String::from("(Best)"),
+        };
+
+        format!(
+            "{move_number} {} {best_move}  {} -> {}",
+            self.played_move, self.eval_before, self.eval_after
+        )
+    }
+}
+
+/// Find the plies with the largest swings in expected score, largest first
+#[must_use]
+pub fn find_key_moments(eval_history: &EvalHistory, amount: usize) -> Vec<KeyMoment> {
+    let mut key_moments = (1..eval_history.len())
+        .filter_map(|ply| {
+            let eval_before = eval_history.get_eval(ply - 1)?;
+            let record = eval_history.get(ply)?;
+            let eval_after = record.eval?;
+
+            Some(KeyMoment {
+                ply,
+                played_move: record.played_move.clone()?,
+                best_move: eval_history.get(ply - 1)?.best_line.first().cloned(),
+                eval_before,
+                eval_after,
+                swing: (eval_after.to_expected_score() - eval_before.to_expected_score()).abs(),
+            })
+        })
+        .filter(|key_moment| key_moment.swing >= KEY_MOMENT_MIN_SWING)
+        .collect::<Vec<_>>();
+
+    key_moments.sort_by(|a, b| b.swing.total_cmp(&a.swing));
+    key_moments.truncate(amount);
+
+    key_moments
+}
+
+#[derive(Component)]
+pub struct KeyMomentsPanel;
+
+#[derive(Component)]
+pub struct KeyMomentEntry {
+    pub ply: usize,
+}
+
+#[derive(Event)]
+pub struct KeyMomentsToggleEvent;
+
+pub fn create_key_moments_panel(mut commands: Commands) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: PANEL_TOP,
+                right: Val::Px(0.),
+                width: PANEL_WIDTH,
+                flex_direction: FlexDirection::Column,
+                padding: UiRect::all(Val::Px(8.)),
+                row_gap: Val::Px(4.),
+                ..default()
+            },
+            BackgroundColor(PANEL_COLOUR),
+            Visibility::Hidden,
+            KeyMomentsPanel,
+            SidePanel,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("Key Moments"),
+                TextFont {
+                    font_size: FONT_SIZE * 1.2,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+            ));
+        });
+}
+
+/// Rebuild the list of key moments whenever the eval history changes
+#[allow(clippy::needless_pass_by_value)]
+pub fn update_key_moments_panel(
+    mut commands: Commands,
+    eval_history: Res<EvalHistory>,
+    panel_query: Query<Entity, With<KeyMomentsPanel>>,
+    entry_entities: Query<Entity, With<KeyMomentEntry>>,
+) {
+    if !eval_history.is_changed() {
+        return;
+    }
+
+    let Ok(panel) = panel_query.single() else {
+        return;
+    };
+
+    for entity in entry_entities.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    commands.entity(panel).with_children(|parent| {
+        for key_moment in find_key_moments(&eval_history, KEY_MOMENT_AMT) {
+            parent
+                .spawn((
+                    Button,
+                    Node {
+                        padding: UiRect::all(Val::Px(4.)),
+                        ..default()
+                    },
+                    BackgroundColor(ENTRY_COLOUR),
+                    KeyMomentEntry { ply: key_moment.ply },
+                ))
+                .with_children(|entry| {
+                    entry.spawn((
+                        Text::new(key_moment.describe()),
+                        TextFont {
+                            font_size: FONT_SIZE,
+                            ..default()
+                        },
+                        TextColor(Color::WHITE),
+                    ));
+                });
+        }
+    });
+}
+
+/// Jump the board to the ply of any key moment which was clicked
+#[allow(clippy::needless_pass_by_value)]
+pub fn key_moments_interaction_handler(
+    interaction_query: Query<(&Interaction, &KeyMomentEntry), Changed<Interaction>>,
+    mut target_ply: ResMut<TargetPly>,
+) {
+    for (interaction, entry) in &interaction_query {
+        if *interaction == Interaction::Pressed {
+            target_ply.jump_to(entry.ply);
+        }
+    }
+}
+
+pub fn key_moments_toggle_event_handler(
+    mut ev_toggle: EventReader<KeyMomentsToggleEvent>,
+    mut panel_query: Query<&mut Visibility, With<KeyMomentsPanel>>,
+) {
+    for _ in ev_toggle.read() {
+        for mut visibility in &mut panel_query {
+            *visibility = match *visibility {
+                Visibility::Hidden => Visibility::Inherited,
+                _ => Visibility::Hidden,
+            };
+        }
+    }
+}
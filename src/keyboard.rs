@@ -2,19 +2,49 @@ use bevy::{input::keyboard::KeyboardInput, prelude::*};
 
 use chess_core::piece::Piece;
 
-use crate::{bitboard_event::BitBoardDisplayEvent, move_history::MoveHistoryEvent};
+use crate::{
+    bitboard_event::BitBoardDisplayEvent,
+    board::BoardBevy,
+    display::BoardFlipEvent,
+    eco::EcoDatabase,
+    eval_graph::EvalHistory,
+    hint::{HintEvent, HintState},
+    key_moments::KeyMomentsToggleEvent,
+    move_history::MoveHistoryEvent,
+    perft::DEBUG_PERFT_DEPTH,
+    pgn::{export_annotated_pgn, game_moves},
+    theme::ThemeCycleEvent,
+    threat::ThreatToggleEvent,
+    tournament::TournamentStartEvent,
+    uci::{transmit_to_uci, UciMessage},
+};
 
 #[derive(Resource, Clone, Default)]
 pub struct KeyboardState {
     pub shift_pressed: bool,
 }
 
+/// # Panics
+/// Panics if the move history can't be converted to a string to send to via uci to the engine
+/// Panics if message cannot be sent via uci
 #[allow(clippy::too_many_lines)]
+#[allow(clippy::too_many_arguments)]
+#[allow(clippy::needless_pass_by_value)]
 pub fn keyboard_event_handler(
     mut keyboard_state: ResMut<KeyboardState>,
     mut ev_keyboard: EventReader<KeyboardInput>,
     mut ev_display_event: EventWriter<BitBoardDisplayEvent>,
     mut ev_move_history: EventWriter<MoveHistoryEvent>,
+    mut ev_key_moments_toggle: EventWriter<KeyMomentsToggleEvent>,
+    mut ev_hint: EventWriter<HintEvent>,
+    mut ev_threat_toggle: EventWriter<ThreatToggleEvent>,
+    mut ev_tournament_start: EventWriter<TournamentStartEvent>,
+    mut ev_board_flip: EventWriter<BoardFlipEvent>,
+    mut ev_theme_cycle: EventWriter<ThemeCycleEvent>,
+    board: Res<BoardBevy>,
+    eval_history: Res<EvalHistory>,
+    hint_state: Res<HintState>,
+    eco_database: Res<EcoDatabase>,
 ) {
     for ev in ev_keyboard.read() {
         if ev.state.is_pressed() {
@@ -108,6 +138,74 @@ pub fn keyboard_event_handler(
                 ev_display_event.write(BitBoardDisplayEvent::new(None, keyboard_state.shift_pressed, false, 3));
             }
 
+            if ev.key_code == KeyCode::KeyG {
+                // Ask the engine to review every move in the game
+                transmit_to_uci(UciMessage::ReviewGame {
+                    move_history: board
+                        .board
+                        .move_history
+                        .to_piece_move_string()
+                        .expect("Could not convert move history into piece move string"),
+                })
+                .unwrap_or_else(|e| panic!("{e}"));
+            }
+
+            if ev.key_code == KeyCode::KeyD {
+                // Print a perft divide of the current position, compared against the engine's
+                transmit_to_uci(UciMessage::Perft {
+                    move_history: board
+                        .board
+                        .move_history
+                        .to_piece_move_string()
+                        .expect("Could not convert move history into piece move string"),
+                    depth: DEBUG_PERFT_DEPTH,
+                })
+                .unwrap_or_else(|e| panic!("{e}"));
+            }
+
+            if ev.key_code == KeyCode::KeyP {
+                // Save the game, annotated with any evals and classifications
+                let moves = game_moves(&board.board, &eval_history);
+                let mut headers = eco_database.headers(&moves);
+                headers.extend(hint_state.headers());
+
+                match export_annotated_pgn(&moves, &eval_history, &headers) {
+                    Ok(path) => println!("Saved annotated game to {}", path.display()),
+                    Err(e) => eprintln!("PGN export error: {e}"),
+                }
+            }
+
+            if ev.key_code == KeyCode::KeyH {
+                // Shift+H switches between revealing the piece to move first, and revealing the whole move
+                ev_hint.write(if keyboard_state.shift_pressed {
+                    HintEvent::ToggleRevealPieceFirst
+                } else {
+                    HintEvent::Request
+                });
+            }
+
+            if ev.key_code == KeyCode::KeyT {
+                ev_threat_toggle.write(ThreatToggleEvent);
+            }
+
+            if ev.key_code == KeyCode::KeyM {
+                ev_tournament_start.write(TournamentStartEvent);
+            }
+
+            if ev.key_code == KeyCode::KeyF {
+                ev_board_flip.write(BoardFlipEvent);
+            }
+
+            if ev.key_code == KeyCode::KeyB {
+                ev_theme_cycle.write(ThemeCycleEvent {
+                    backwards: keyboard_state.shift_pressed,
+                });
+            }
+
+            if ev.key_code == KeyCode::KeyK {
+                ev_key_moments_toggle.write(KeyMomentsToggleEvent);
+            }
+
             if ev.key_code == KeyCode::ArrowLeft {
                 ev_move_history.write(MoveHistoryEvent { backwards: true });
             }
@@ -2,7 +2,8 @@ use bevy::prelude::*;
 
 use crate::{
     board::BoardBevy,
-    display::{board_to_pixel_coords, PIECE_SIZE},
+    display::{BoardOrientation, PIECE_SIZE},
+    theme::ThemeState,
 };
 
 #[derive(Event)]
@@ -17,6 +18,8 @@ pub fn last_move_event_handler(
     board: Res<BoardBevy>,
     last_move_entities: Query<Entity, With<LastMoveMarker>>,
     mut commands: Commands,
+    theme_state: Res<ThemeState>,
+    orientation: Res<BoardOrientation>,
 ) {
     for _ in ev_last_move.read() {
         // Clear any last_move entities
@@ -31,15 +34,15 @@ pub fn last_move_event_handler(
         let (last_move, _, _, _) = last_move.into();
 
         let xy = [
-            board_to_pixel_coords(last_move.from.file, last_move.from.rank),
-            board_to_pixel_coords(last_move.to.file, last_move.to.rank),
+            orientation.board_to_pixel_coords(last_move.from.file, last_move.from.rank),
+            orientation.board_to_pixel_coords(last_move.to.file, last_move.to.rank),
         ];
 
         // Spawn an entity for the from and to positions of this piece move
         for (x, y) in xy {
             commands.spawn((
                 Sprite {
-                    color: Color::linear_rgba(0.8, 0.8, 0.1, 1.0),
+                    color: theme_state.theme().last_move.0,
                     ..default()
                 },
                 Transform::from_xyz(x, y, 0.5).with_scale(Vec3::splat(PIECE_SIZE * 1.0)),
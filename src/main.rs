@@ -5,47 +5,170 @@
 #![allow(clippy::cast_precision_loss)]
 #![allow(clippy::cast_possible_truncation)]
 
+use std::{path::PathBuf, time::Duration};
+
 use bevy::prelude::*;
 
 use crate::{
+    animation::{animate_pieces, set_animation_options},
+    batch::run_batch_analysis,
     bitboard_event::{bitboard_event_handler, BitBoardDisplayEvent},
-    board::BoardBevy,
-    display::{background_colour_event_handler, display_board, BackgroundColourEvent},
+    board::{set_start_fen, BoardBevy},
+    book::set_book_options,
+    captured::{create_captured_panel, position_captured_panel, update_captured_panel},
+    check::update_check_highlight,
+    display::{
+        background_colour_event_handler, board_flip_event_handler, display_board, layout_board, update_coordinate_labels,
+        BackgroundColourEvent, BoardFlipEvent, BoardLayout, BoardOrientation,
+    },
+    drawing::{drawing_input_handler, update_drawings},
+    eco::{create_opening_label, update_opening_label, EcoDatabase},
+    epd_suite::run_epd_suite_cli,
     eval_bar::{create_eval_bar, update_eval_bar, CurrentEval},
+    eval_graph::{create_eval_graph, eval_graph_interaction_handler, update_eval_graph, EvalHistory},
     game_end::{game_end_event_handler, GameEndEvent},
+    hint::{create_hint_mode_label, hint_event_handler, update_hint_display, update_hint_mode_label, HintEvent, HintState},
+    key_moments::{
+        create_key_moments_panel, key_moments_interaction_handler, key_moments_toggle_event_handler, update_key_moments_panel,
+        KeyMomentsToggleEvent,
+    },
     keyboard::{keyboard_event_handler, KeyboardState},
     last_move::{last_move_event_handler, LastMoveEvent},
-    move_history::{move_history_event_handler, MoveHistoryEvent},
+    move_history::{jump_to_ply_handler, move_history_event_handler, MoveHistoryEvent, TargetPly},
+    perft::run_perft_suite_cli,
     piece_move::{piece_move_event_handler, PieceMoveEvent},
     possible_moves::{possible_move_event_handler, PossibleMoveDisplayEvent},
-    uci::communicate_to_uci,
+    premove::{clear_premoves_handler, premove_handler, update_premove_markers, PremoveQueue},
+    promotion::{
+        promotion_picker_click_handler, promotion_picker_event_handler, AutoQueen, PromotionPickerEvent, PromotionState,
+    },
+    selection::{click_to_move_handler, update_selection_marker, PieceSelection},
+    sprt::run_sprt_cli,
+    theme::{theme_cycle_event_handler, ThemeCycleEvent, ThemeState},
+    threat::{threat_toggle_event_handler, update_threat_display, ThreatState, ThreatToggleEvent},
+    tournament::{
+        create_tournament_panel, run_tournament_cli, tournament_start_event_handler, update_tournament_panel,
+        TournamentStartEvent, TournamentState,
+    },
+    uci::{communicate_to_uci, transmit_to_uci, UciMessage, ENGINE_PLAYER},
     uci_event::{process_uci_to_board_threads, uci_to_board_event_handler, UciEvent},
 };
 
+pub mod animation;
+pub mod arrow;
+pub mod batch;
 pub mod bitboard_event;
 pub mod board;
+pub mod book;
+pub mod captured;
+pub mod check;
 pub mod classification;
 pub mod display;
+pub mod drawing;
+pub mod eco;
+pub mod epd_suite;
 pub mod eval_bar;
+pub mod eval_cache;
+pub mod eval_graph;
 pub mod game_end;
+pub mod hint;
+pub mod key_moments;
 pub mod keyboard;
 pub mod last_move;
 pub mod move_history;
+pub mod perft;
+pub mod pgn;
 pub mod piece;
 pub mod piece_move;
 pub mod possible_moves;
+pub mod premove;
+pub mod promotion;
+pub mod puzzle;
+pub mod selection;
+pub mod sprt;
+pub mod theme;
+pub mod threat;
+pub mod tournament;
 pub mod uci;
 pub mod uci_event;
 pub mod uci_info;
 
+/// Set the position games start from (``--fen``), the opening book the engine plays from (``--book`` and ``--book-depth``),
+/// and how pieces are animated (``--animation-ms`` and ``--easing``), returning whether promotions skip the picker (``--auto-queen``)
+fn set_gui_options(args: &[String]) -> Result<AutoQueen, String> {
+    let mut auto_queen = AutoQueen::default();
+    let mut book_path = None;
+    let mut book_depth = None;
+    let mut animation_duration = None;
+    let mut easing = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next().ok_or_else(|| {
+                format!("Usage: chess_gui [--fen <fen>] [--book <polyglot file>] [--book-depth <plies>] [--animation-ms <ms>] [--easing <easing>] [--auto-queen]\n\tMissing {arg} value")
+            })
+        };
+
+        match arg.as_str() {
+            "--fen" => set_start_fen(value()?)?,
+            "--book" => book_path = Some(PathBuf::from(value()?)),
+            "--book-depth" => book_depth = Some(value()?.parse().map_err(|_| String::from("Invalid --book-depth value"))?),
+            "--animation-ms" => {
+                animation_duration = Some(Duration::from_millis(
+                    value()?.parse().map_err(|_| String::from("Invalid --animation-ms value"))?,
+                ));
+            }
+            "--easing" => easing = Some(value()?.parse()?),
+            "--auto-queen" => auto_queen.0 = true,
+            _ => return Err(format!("Unknown option {arg}")),
+        }
+    }
+
+    set_book_options(book_path, book_depth)?;
+    set_animation_options(animation_duration, easing)?;
+
+    Ok(auto_queen)
+}
+
+#[allow(clippy::too_many_lines)]
 fn main() {
+    // Run headless subcommands without opening a window
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let mut auto_queen = AutoQueen::default();
+    let subcommand_result = match args.first().map(String::as_str) {
+        Some("analyse") => Some(run_batch_analysis(&args[1..]).map_err(|e| e.to_string())),
+        Some("tournament") => Some(run_tournament_cli(&args[1..]).map_err(|e| e.to_string())),
+        Some("sprt") => Some(run_sprt_cli(&args[1..]).map_err(|e| e.to_string())),
+        Some("epd") => Some(run_epd_suite_cli(&args[1..]).map_err(|e| e.to_string())),
+        Some("perft") => Some(run_perft_suite_cli(&args[1..]).map_err(|e| e.to_string())),
+        // Options for the GUI, which is then started as normal
+        Some(arg) if arg.starts_with("--") => match set_gui_options(&args) {
+            Ok(gui_auto_queen) => {
+                auto_queen = gui_auto_queen;
+                None
+            }
+            Err(e) => Some(Err(e)),
+        },
+        _ => None,
+    };
+
+    if let Some(result) = subcommand_result {
+        if let Err(e) = result {
+            eprintln!("{e}");
+            std::process::exit(1);
+        }
+
+        return;
+    }
+
     App::new()
         .add_plugins((DefaultPlugins
             .set(ImagePlugin::default_nearest())
             .set(WindowPlugin {
                 primary_window: Some(Window {
                     title: "Chez.cum".into(),
-                    resolution: (1920., 1280.).into(),
+                    resolution: (1280., 960.).into(),
                     resizable: true,
                     ..default()
                 }),
@@ -60,11 +183,44 @@ fn main() {
         .add_event::<GameEndEvent>()
         .add_event::<UciEvent>()
         .add_event::<LastMoveEvent>()
+        .add_event::<KeyMomentsToggleEvent>()
+        .add_event::<HintEvent>()
+        .add_event::<ThreatToggleEvent>()
+        .add_event::<TournamentStartEvent>()
+        .add_event::<BoardFlipEvent>()
+        .add_event::<ThemeCycleEvent>()
+        .add_event::<PromotionPickerEvent>()
         .init_resource::<BoardBevy>()
+        .init_resource::<BoardOrientation>()
         .init_resource::<KeyboardState>()
         .init_resource::<CurrentEval>()
+        .init_resource::<EvalHistory>()
+        .init_resource::<TargetPly>()
+        .init_resource::<HintState>()
+        .init_resource::<ThreatState>()
+        .init_resource::<TournamentState>()
+        .init_resource::<EcoDatabase>()
+        .init_resource::<BoardLayout>()
+        .init_resource::<ThemeState>()
+        .init_resource::<PieceSelection>()
+        .init_resource::<PromotionState>()
+        .init_resource::<PremoveQueue>()
+        .insert_resource(auto_queen)
         .insert_resource(communicate_to_uci())
-        .add_systems(Startup, (setup, display_board, create_eval_bar))
+        .add_systems(
+            Startup,
+            (
+                setup,
+                display_board,
+                create_eval_bar,
+                create_eval_graph,
+                create_captured_panel,
+                create_key_moments_panel,
+                create_tournament_panel,
+                create_opening_label,
+                create_hint_mode_label,
+            ),
+        )
         .add_systems(PreUpdate, process_uci_to_board_threads)
         .add_systems(
             Update,
@@ -79,33 +235,77 @@ fn main() {
                 uci_to_board_event_handler,
                 last_move_event_handler,
                 update_eval_bar,
+                update_eval_graph,
+                eval_graph_interaction_handler,
+                jump_to_ply_handler.before(move_history_event_handler),
+                update_key_moments_panel,
+                key_moments_interaction_handler,
+                key_moments_toggle_event_handler,
+            ),
+        )
+        .add_systems(
+            Update,
+            (
+                hint_event_handler,
+                update_hint_display,
+                update_hint_mode_label,
+                threat_toggle_event_handler,
+                update_threat_display,
+                tournament_start_event_handler,
+                update_tournament_panel,
+                update_opening_label,
+                board_flip_event_handler,
+                layout_board,
+                update_coordinate_labels,
+                theme_cycle_event_handler,
+                animate_pieces,
+                click_to_move_handler.after(promotion_picker_click_handler),
+                promotion_picker_event_handler,
+                promotion_picker_click_handler.before(piece_move_event_handler),
+                premove_handler.after(uci_to_board_event_handler),
+                clear_premoves_handler,
+                update_premove_markers,
+                update_selection_marker.after(click_to_move_handler),
+            ),
+        )
+        .add_systems(
+            Update,
+            (
+                drawing_input_handler.before(clear_premoves_handler),
+                update_drawings,
+                update_check_highlight,
+                update_captured_panel,
+                position_captured_panel.before(layout_board),
             ),
         )
         .run();
 }
 
+/// # Panics
+/// Panics if the engine should move first, but the move can't be sent to it
 #[allow(clippy::needless_pass_by_value)]
-fn setup(
-    mut commands: Commands,
-    board: Res<BoardBevy>,
-    mut background_ev: EventWriter<BackgroundColourEvent>,
-    windows: Query<&Window>,
-) {
-    let Ok(window) = windows.single() else {
-        return;
-    };
-    let scale_factor = window.scale_factor();
-    println!("Scale factor: {scale_factor}");
-    let mut projection = OrthographicProjection::default_2d();
-    projection.scale *= scale_factor;
-
+fn setup(mut commands: Commands, board: Res<BoardBevy>, mut background_ev: EventWriter<BackgroundColourEvent>) {
+    // The projection is zoomed to fit the board by layout_board
     commands.spawn((
         Camera2d,
         Camera::default(),
-        Projection::custom(projection),
+        Projection::Orthographic(OrthographicProjection::default_2d()),
         Transform::default(),
         GlobalTransform::default(),
     ));
 
     background_ev.write(BackgroundColourEvent::new_from_player(board.board.get_player()));
+
+    // The engine moves first when the starting position has it to move
+    if board.board.get_player() == ENGINE_PLAYER {
+        transmit_to_uci(UciMessage::NewMove {
+            move_history: board
+                .board
+                .move_history
+                .to_piece_move_string()
+                .expect("Could not convert move history into piece move string"),
+            player_to_move: board.board.get_player(),
+        })
+        .unwrap_or_else(|e| panic!("{e}"));
+    }
 }
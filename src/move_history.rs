@@ -1,7 +1,12 @@
 use bevy::prelude::*;
 
 use crate::{
-    board::BoardBevy, display::BackgroundColourEvent, game_end::GameEndEvent, last_move::LastMoveEvent, uci_event::UciEvent,
+    board::BoardBevy,
+    display::{BackgroundColourEvent, BoardOrientation},
+    game_end::GameEndEvent,
+    last_move::LastMoveEvent,
+    theme::ThemeState,
+    uci_event::UciEvent,
 };
 
 #[derive(Event)]
@@ -9,6 +14,20 @@ pub struct MoveHistoryEvent {
     pub backwards: bool,
 }
 
+/// The ply which the board should be moved to, one move per frame
+#[derive(Resource, Default)]
+pub struct TargetPly {
+    pub target: Option<usize>,
+    previous_ply: Option<usize>,
+}
+
+impl TargetPly {
+    pub const fn jump_to(&mut self, ply: usize) {
+        self.target = Some(ply);
+        self.previous_ply = None;
+    }
+}
+
 #[allow(clippy::needless_pass_by_value)]
 #[allow(clippy::too_many_arguments)]
 pub fn move_history_event_handler(
@@ -23,6 +42,8 @@ pub fn move_history_event_handler(
     mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
     mut sprites: Query<&mut Sprite>,
     mut uci_to_board_ev: EventWriter<UciEvent>,
+    theme_state: Res<ThemeState>,
+    orientation: Res<BoardOrientation>,
 ) {
     for ev in move_history_ev.read() {
         // Traverse the history in the specified direction
@@ -47,6 +68,8 @@ pub fn move_history_event_handler(
                 &mut background_ev,
                 &mut last_move_ev,
                 &mut uci_to_board_ev,
+                theme_state.theme(),
+                *orientation,
                 history_move,
             );
         } else {
@@ -59,8 +82,35 @@ pub fn move_history_event_handler(
                 &mut background_ev,
                 &mut game_end_ev,
                 &mut last_move_ev,
+                *orientation,
                 piece_move_original.with_show(false),
             );
         }
     }
 }
+
+/// Step through the move history until the board reaches the ``TargetPly``
+#[allow(clippy::needless_pass_by_value)]
+pub fn jump_to_ply_handler(
+    mut target_ply: ResMut<TargetPly>,
+    board: Res<BoardBevy>,
+    mut move_history_ev: EventWriter<MoveHistoryEvent>,
+) {
+    let Some(target) = target_ply.target else {
+        return;
+    };
+
+    let current_ply = board.current_ply();
+
+    // Stop if the target has been reached, or if the last step didn't change the ply (Target is outside of the history)
+    if target == current_ply || target_ply.previous_ply == Some(current_ply) {
+        target_ply.target = None;
+        return;
+    }
+
+    target_ply.previous_ply = Some(current_ply);
+
+    move_history_ev.write(MoveHistoryEvent {
+        backwards: target < current_ply,
+    });
+}
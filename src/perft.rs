@@ -0,0 +1,282 @@
+use std::{fs, path::PathBuf, time::Instant};
+
+use thiserror::Error;
+
+use chess_core::board::Board;
+
+use crate::{
+    board::get_all_possible_moves,
+    uci::{UciEngine, UciError},
+};
+
+/// Depth used by the perft debug key in the GUI
+pub const DEBUG_PERFT_DEPTH: u32 = 3;
+const DEFAULT_MAX_DEPTH: u32 = 4;
+
+#[derive(Error, Debug)]
+pub enum PerftError {
+    #[error("Usage: chess_gui perft <epd file> [--engine <command>] [--depth <max depth>]\n\t{0}")]
+    UsageError(String),
+
+    #[error("Perft file could not be read:\n\t{0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error(transparent)]
+    UciError(#[from] UciError),
+
+    #[error("Perft line {0} could not be parsed:\n\t{1}")]
+    PerftParseError(usize, String),
+}
+
+/// Count the leaf nodes of the legal move tree to ``depth``
+#[must_use]
+pub fn perft(board: &Board, depth: u32) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+
+    let moves = get_all_possible_moves(board);
+    if depth == 1 {
+        return moves.len() as u64;
+    }
+
+    moves
+        .into_iter()
+        .map(|piece_move| {
+            let mut board = board.clone();
+            board.apply_move(piece_move);
+
+            perft(&board, depth - 1)
+        })
+        .sum()
+}
+
+/// Perft of the position after each legal move, keyed by the move in long algebraic notation
+/// # Errors
+/// Returns an error if any legal move can't be converted to long algebraic notation, rather than leaving it out of the divide
+pub fn divide(board: &Board, depth: u32) -> Result<Vec<(String, u64)>, UciError> {
+    let mut divide = get_all_possible_moves(board)
+        .into_iter()
+        .map(|piece_move| {
+            let algebraic = piece_move
+                .to_algebraic()
+                .map_err(|e| UciError::PieceMoveParseError(format!("{piece_move}: {e}")))?;
+
+            let mut board_after = board.clone();
+            board_after.apply_move(piece_move);
+
+            Ok((algebraic, perft(&board_after, depth.saturating_sub(1))))
+        })
+        .collect::<Result<Vec<_>, UciError>>()?;
+
+    divide.sort();
+    Ok(divide)
+}
+
+/// A move whose node count is different between ``chess_core`` and the engine (``None`` if one of them doesn't think it is legal)
+#[derive(Debug, Clone)]
+pub struct DivideDifference {
+    pub algebraic: String,
+    pub chess_core: Option<u64>,
+    pub engine: Option<u64>,
+}
+
+/// Find every move where the node counts of two divides don't match
+#[must_use]
+pub fn compare_divides(chess_core: &[(String, u64)], engine: &[(String, u64)]) -> Vec<DivideDifference> {
+    let find =
+        |divide: &[(String, u64)], algebraic: &str| divide.iter().find(|(other, _)| other == algebraic).map(|(_, nodes)| *nodes);
+
+    let mut moves = chess_core
+        .iter()
+        .chain(engine)
+        .map(|(algebraic, _)| algebraic.clone())
+        .collect::<Vec<_>>();
+    moves.sort();
+    moves.dedup();
+
+    moves
+        .into_iter()
+        .map(|algebraic| DivideDifference {
+            chess_core: find(chess_core, &algebraic),
+            engine: find(engine, &algebraic),
+            algebraic,
+        })
+        .filter(|difference| difference.chess_core != difference.engine)
+        .collect()
+}
+
+/// Format the differences between two divides as a table
+#[must_use]
+pub fn describe_differences(differences: &[DivideDifference]) -> String {
+    if differences.is_empty() {
+        return String::from("chess_core and the engine agree on every move");
+    }
+
+    let format_nodes = |nodes: Option<u64>| nodes.map_or_else(|| String::from("Illegal"), |nodes| nodes.to_string());
+
+    std::iter::once(format!("{:<8}{:>14}{:>14}", "Move", "chess_core", "Engine"))
+        .chain(differences.iter().map(|difference| {
+            format!(
+                "{:<8}{:>14}{:>14}",
+                difference.algebraic,
+                format_nodes(difference.chess_core),
+                format_nodes(difference.engine)
+            )
+        }))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Divide ``board`` with both ``chess_core`` and the engine (Given ``position`` as the arguments of a UCI ``position`` command), printing any differences
+/// # Errors
+/// Returns an error if the engine can't be communicated with
+/// Returns an error if any of ``chess_core``'s moves can't be converted to long algebraic notation
+pub fn debug_perft(engine: &mut UciEngine, board: &Board, position: &str, depth: u32) -> Result<(), UciError> {
+    let perft_start = Instant::now();
+    let chess_core_divide = divide(board, depth)?;
+    let chess_core_nodes = chess_core_divide.iter().map(|(_, nodes)| nodes).sum::<u64>();
+
+    println!(
+        "Perft({depth}) with chess_core: {chess_core_nodes} nodes in {:.2}s",
+        perft_start.elapsed().as_secs_f32()
+    );
+
+    let engine_divide = engine.perft(position, depth)?;
+    if engine_divide.is_empty() {
+        println!("The engine doesn't support \"go perft\"");
+        return Ok(());
+    }
+
+    println!(
+        "Perft({depth}) with the engine: {} nodes\n{}",
+        engine_divide.iter().map(|(_, nodes)| nodes).sum::<u64>(),
+        describe_differences(&compare_divides(&chess_core_divide, &engine_divide))
+    );
+
+    Ok(())
+}
+
+/// A position from a perft suite, with the expected node count at each depth
+#[derive(Debug, Clone)]
+pub struct PerftPosition {
+    pub fen: String,
+    pub expected: Vec<(u32, u64)>,
+}
+
+/// Parse a line of a perft EPD file (e.g. ``<fen> ;D1 20 ;D2 400``)
+/// # Errors
+/// Returns an error if the FEN or the depths can't be parsed
+pub fn parse_perft_line(line: &str) -> Result<PerftPosition, String> {
+    let mut parts = line.split(';');
+
+    let fen_fields = parts.next().unwrap_or_default().split_whitespace().collect::<Vec<_>>();
+    let fen = match fen_fields.len() {
+        4 => format!("{} 0 1", fen_fields.join(" ")),
+        6 => fen_fields.join(" "),
+        _ => return Err(format!("Invalid FEN \"{}\"", fen_fields.join(" "))),
+    };
+    Board::from_fen(&fen)?;
+
+    let expected = parts
+        .map(|part| {
+            let (depth, nodes) = part
+                .trim()
+                .split_once(char::is_whitespace)
+                .ok_or_else(|| format!("Invalid depth \"{part}\""))?;
+
+            Ok((
+                depth
+                    .trim_start_matches('D')
+                    .parse()
+                    .map_err(|_| format!("Invalid depth \"{depth}\""))?,
+                nodes.trim().parse().map_err(|_| format!("Invalid node count \"{nodes}\""))?,
+            ))
+        })
+        .collect::<Result<_, String>>()?;
+
+    Ok(PerftPosition { fen, expected })
+}
+
+/// Check the node counts of ``chess_core`` against every position in a perft suite
+/// When an engine is given, any wrong counts are divided against the engine to find the moves which are wrong
+/// # Errors
+/// Returns an error if the arguments are invalid, or the suite can't be loaded
+/// Returns an error if the engine can't be communicated with
+pub fn run_perft_suite_cli(args: &[String]) -> Result<(), PerftError> {
+    let mut suite_path = None;
+    let mut engine_command = None;
+    let mut max_depth = DEFAULT_MAX_DEPTH;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--engine" => {
+                engine_command = Some(
+                    args.next()
+                        .ok_or_else(|| PerftError::UsageError(String::from("Missing --engine value")))?,
+                );
+            }
+            "--depth" => {
+                max_depth = args
+                    .next()
+                    .and_then(|depth| depth.parse().ok())
+                    .ok_or_else(|| PerftError::UsageError(String::from("Missing or invalid --depth value")))?;
+            }
+            _ => suite_path = Some(PathBuf::from(arg)),
+        }
+    }
+
+    let suite_path = suite_path.ok_or_else(|| PerftError::UsageError(String::from("Missing EPD file")))?;
+    let positions = fs::read_to_string(&suite_path)?
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+        .map(|(i, line)| parse_perft_line(line).map_err(|e| PerftError::PerftParseError(i + 1, e)))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut engine = engine_command.map(|command| UciEngine::start(command)).transpose()?;
+
+    let mut passed = 0;
+    let mut failed = 0;
+
+    for (i, position) in positions.iter().enumerate() {
+        let Ok(board) = Board::from_fen(&position.fen) else {
+            continue;
+        };
+
+        for &(depth, expected_nodes) in position.expected.iter().filter(|(depth, _)| *depth <= max_depth) {
+            let nodes = perft(&board, depth);
+
+            if nodes == expected_nodes {
+                passed += 1;
+                println!("[{}/{}] D{depth} {nodes} Passed", i + 1, positions.len());
+                continue;
+            }
+
+            failed += 1;
+            println!(
+                "[{}/{}] D{depth} {nodes} Failed, expected {expected_nodes}\n\tFEN: {}",
+                i + 1,
+                positions.len(),
+                position.fen
+            );
+
+            // Pin the difference down to the moves which are wrong
+            if let Some(engine) = engine.as_mut() {
+                debug_perft(engine, &board, &format!("fen {}", position.fen), depth)?;
+            }
+
+            // Deeper counts will be wrong too
+            break;
+        }
+    }
+
+    if let Some(engine) = engine.as_mut() {
+        engine.quit()?;
+    }
+
+    println!("{passed} passed, {failed} failed");
+
+    Ok(())
+}
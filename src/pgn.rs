@@ -0,0 +1,578 @@
+use std::{
+    fmt::Write,
+    fs,
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use thiserror::Error;
+
+use chess_core::{
+    board::{Board, Player, TilePos, DEFAULT_FEN},
+    piece::Piece,
+    piece_move::{PieceMove, PieceMoveType},
+};
+
+use crate::{
+    board::{get_all_possible_moves, is_in_check, start_board, start_fen},
+    drawing::Drawing,
+    eval_graph::EvalHistory,
+    uci::{ENGINE_COMMAND, ENGINE_PLAYER, REVIEW_DEPTH},
+    uci_info::UciEval,
+};
+
+pub const PGN_EXPORT_DIR: &str = "games";
+
+const PGN_LINE_WIDTH: usize = 80;
+/// Best move variations are cut off after this many plies
+const MAX_VARIATION_PLIES: usize = 6;
+
+#[derive(Error, Debug)]
+pub enum PgnError {
+    #[error("PGN file could not be read/wrote to:\n\t{0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("Piece move could not be parsed:\n\t{0}")]
+    PieceMoveParseError(String),
+
+    #[error("Move {0} is not legal in this position")]
+    IllegalMove(String),
+
+    #[error("FEN could not be parsed:\n\t{0}")]
+    FenParseError(String),
+}
+
+/// Find the legal move in this position which matches a move in long algebraic notation (e.g. ``e7e8q``)
+/// # Errors
+/// Returns an error if the move cannot be parsed, or if it isn't legal
+pub fn find_legal_move(board: &Board, algebraic: &str) -> Result<PieceMove, PgnError> {
+    let piece_move = PieceMove::from_algebraic(algebraic).map_err(PgnError::PieceMoveParseError)?;
+
+    let candidates = board
+        .get_possible_moves(piece_move.from)
+        .into_iter()
+        .filter(|candidate| candidate.to == piece_move.to)
+        .collect::<Vec<_>>();
+
+    // Promotions have a candidate for each piece, so match the full notation when there is more than one candidate
+    candidates
+        .iter()
+        .find(|candidate| candidate.to_algebraic().is_ok_and(|candidate| candidate == algebraic))
+        .or_else(|| candidates.first())
+        .copied()
+        .ok_or_else(|| PgnError::IllegalMove(algebraic.to_string()))
+}
+
+#[must_use]
+pub fn tile_to_string(pos: TilePos) -> String {
+    format!("{}{}", file_to_char(pos.file), pos.rank + 1)
+}
+
+const fn file_to_char(file: u32) -> char {
+    (b'a' + file as u8) as char
+}
+
+/// The SAN letter for a piece, or ``None`` for pawns
+#[must_use]
+pub const fn piece_letter(piece: Piece) -> Option<char> {
+    match piece {
+        Piece::WKnight | Piece::BKnight => Some('N'),
+        Piece::WBishop | Piece::BBishop => Some('B'),
+        Piece::WRook | Piece::BRook => Some('R'),
+        Piece::WQueen | Piece::BQueen => Some('Q'),
+        Piece::WKing | Piece::BKing => Some('K'),
+        _ => None,
+    }
+}
+
+/// Convert a legal move into Standard Algebraic Notation (e.g. ``Nbxd7+``)
+#[must_use]
+pub fn to_san(board: &Board, piece_move: PieceMove) -> String {
+    let mut san = to_san_without_check(board, piece_move);
+
+    // Check if this move gives check or checkmate
+    let mut board_after = board.clone();
+    board_after.apply_move(piece_move);
+
+    if matches!(board_after.has_game_ended(), Some(Some(_))) {
+        san.push('#');
+    } else if is_in_check(&board_after, board_after.get_player()) {
+        san.push('+');
+    }
+
+    san
+}
+
+/// Convert a legal move into Standard Algebraic Notation, without any check or checkmate suffix
+#[must_use]
+#[allow(clippy::option_if_let_else)]
+pub fn to_san_without_check(board: &Board, piece_move: PieceMove) -> String {
+    let piece = board.get_piece(piece_move.from);
+
+    if piece_move.move_type == PieceMoveType::Castling {
+        String::from(if piece_move.from.file < piece_move.to.file {
+            "O-O"
+        } else {
+            "O-O-O"
+        })
+    } else {
+        let is_capture = board.get_piece(piece_move.to) != Piece::None || piece_move.move_type == PieceMoveType::EnPassant;
+        let capture = if is_capture { "x" } else { "" };
+
+        if let Some(letter) = piece_letter(piece) {
+            // Other pieces of the same type which could also move to this tile
+            let ambiguous_froms = get_all_possible_moves(board)
+                .into_iter()
+                .filter(|other| {
+                    other.to == piece_move.to && other.from != piece_move.from && board.get_piece(other.from) == piece
+                })
+                .map(|other| other.from)
+                .collect::<Vec<_>>();
+
+            let disambiguation = if ambiguous_froms.is_empty() {
+                String::new()
+            } else if ambiguous_froms.iter().all(|from| from.file != piece_move.from.file) {
+                file_to_char(piece_move.from.file).to_string()
+            } else if ambiguous_froms.iter().all(|from| from.rank != piece_move.from.rank) {
+                (piece_move.from.rank + 1).to_string()
+            } else {
+                tile_to_string(piece_move.from)
+            };
+
+            format!("{letter}{disambiguation}{capture}{}", tile_to_string(piece_move.to))
+        } else {
+            let pawn_file = if is_capture {
+                file_to_char(piece_move.from.file).to_string()
+            } else {
+                String::new()
+            };
+
+            let promotion = match piece_move.move_type {
+                PieceMoveType::Promotion(promoted_to) => piece_letter(promoted_to).map(|letter| format!("={letter}")),
+                _ => None,
+            }
+            .unwrap_or_default();
+
+            format!("{pawn_file}{capture}{}{promotion}", tile_to_string(piece_move.to))
+        }
+    }
+}
+
+/// Remove anything from a SAN move which doesn't identify the move (Check markers, annotations, and promotion ``=``)
+fn normalise_san(san: &str) -> String {
+    san.replace("0-0-0", "O-O-O")
+        .replace("0-0", "O-O")
+        .chars()
+        .filter(|c| !matches!(c, '+' | '#' | '!' | '?' | '='))
+        .collect()
+}
+
+/// Find the legal move in this position which matches a move in SAN
+/// # Errors
+/// Returns an error if no legal move matches
+pub fn find_legal_move_from_san(board: &Board, san: &str) -> Result<PieceMove, PgnError> {
+    let san = normalise_san(san);
+
+    get_all_possible_moves(board)
+        .into_iter()
+        .find(|piece_move| normalise_san(&to_san_without_check(board, *piece_move)) == san)
+        .ok_or(PgnError::IllegalMove(san))
+}
+
+/// Convert a line of moves in long algebraic notation into SAN, starting from ``board``
+/// # Errors
+/// Returns an error if any of the moves are not legal
+pub fn line_to_san(board: &Board, line: &[String]) -> Result<Vec<String>, PgnError> {
+    let mut board = board.clone();
+
+    line.iter()
+        .map(|algebraic| {
+            let piece_move = find_legal_move(&board, algebraic)?;
+            let san = to_san(&board, piece_move);
+            board.apply_move(piece_move);
+
+            Ok(san)
+        })
+        .collect()
+}
+
+/// The PGN result tag for the game on this board
+#[must_use]
+pub fn game_result(board: &Board) -> &'static str {
+    match board.has_game_ended() {
+        Some(Some(Player::White)) => "1-0",
+        Some(Some(Player::Black)) => "0-1",
+        Some(None) => "1/2-1/2",
+        None => "*",
+    }
+}
+
+/// Format drawings as ``[%csl]`` (Circled tiles) and ``[%cal]`` (Arrows) comment commands, e.g. ``[%cal Ge2e4,Rd7d5]``
+#[must_use]
+pub fn pgn_drawings(drawings: &[Drawing]) -> Vec<String> {
+    let circles = drawings
+        .iter()
+        .filter_map(|drawing| match *drawing {
+            Drawing::Circle { tile, colour } => Some(format!("{}{}", colour.to_letter(), tile_to_string(tile))),
+            Drawing::Arrow { .. } => None,
+        })
+        .collect::<Vec<_>>();
+
+    let arrows = drawings
+        .iter()
+        .filter_map(|drawing| match *drawing {
+            Drawing::Arrow { from, to, colour } => Some(format!(
+                "{}{}{}",
+                colour.to_letter(),
+                tile_to_string(from),
+                tile_to_string(to)
+            )),
+            Drawing::Circle { .. } => None,
+        })
+        .collect::<Vec<_>>();
+
+    [("csl", circles), ("cal", arrows)]
+        .into_iter()
+        .filter(|(_, parts)| !parts.is_empty())
+        .map(|(command, parts)| format!("[%{command} {}]", parts.join(",")))
+        .collect()
+}
+
+/// Format an eval in the way that ``[%eval]`` comments expect (In pawns from White's perspective, or ``#n`` for mates)
+#[must_use]
+pub fn pgn_eval(eval: UciEval) -> String {
+    match eval {
+        UciEval::Centipawn(eval) => format!("{:.2}", eval as f32 / 100.),
+        UciEval::Mate(mate_in) => format!("#{mate_in}"),
+    }
+}
+
+/// The move number prefix for the move which reaches ``ply`` (e.g. ``12.`` or ``12...``)
+#[must_use]
+pub fn move_number(ply: usize, force: bool) -> Option<String> {
+    let number = ply.div_ceil(2);
+
+    // White made the move if it led to an odd ply
+    if !ply.is_multiple_of(2) {
+        Some(format!("{number}."))
+    } else if force {
+        Some(format!("{number}..."))
+    } else {
+        None
+    }
+}
+
+/// Create the default headers for a game played against the engine
+#[must_use]
+pub fn default_headers(result: &str) -> Vec<(String, String)> {
+    let (white, black) = if ENGINE_PLAYER == Player::White {
+        (ENGINE_COMMAND, "Human")
+    } else {
+        ("Human", ENGINE_COMMAND)
+    };
+
+    [
+        ("Event", "Casual Game"),
+        ("Site", "Chess GUI"),
+        ("Date", "????.??.??"),
+        ("Round", "-"),
+        ("White", white),
+        ("Black", black),
+        ("Result", result),
+        ("Annotator", ENGINE_COMMAND),
+    ]
+    .into_iter()
+    .map(|(key, value)| (key.to_string(), value.to_string()))
+    .chain(std::iter::once((String::from("AnnotatorDepth"), REVIEW_DEPTH.to_string())))
+    .chain(
+        // Games which didn't start from the default position need to say where they started
+        (start_fen() != DEFAULT_FEN)
+            .then(|| {
+                [
+                    (String::from("SetUp"), String::from("1")),
+                    (String::from("FEN"), start_fen().to_string()),
+                ]
+            })
+            .into_iter()
+            .flatten(),
+    )
+    .collect()
+}
+
+/// The position a game starts from (Using its ``FEN`` header if it has one), along with the ply which that position is at
+/// # Errors
+/// Returns an error if the ``FEN`` header can't be parsed
+pub fn start_position(headers: &[(String, String)]) -> Result<(Board, usize), PgnError> {
+    let Some((_, fen)) = headers.iter().find(|(key, _)| key == "FEN") else {
+        return Ok((Board::default(), 0));
+    };
+
+    let board = Board::from_fen(fen).map_err(PgnError::FenParseError)?;
+    let start_ply = fen_start_ply(fen, &board);
+
+    Ok((board, start_ply))
+}
+
+/// The ply which ``board`` (Parsed from ``fen``) is at, so that move numbers continue from the FEN's move number
+#[must_use]
+pub fn fen_start_ply(fen: &str, board: &Board) -> usize {
+    let fullmove_number = fen
+        .split_whitespace()
+        .nth(5)
+        .and_then(|number| number.parse::<usize>().ok())
+        .unwrap_or(1)
+        .max(1);
+
+    (fullmove_number - 1) * 2 + usize::from(board.get_player() == Player::Black)
+}
+
+/// Create a PGN of ``moves`` (In long algebraic notation), annotated using the evals and classifications in ``eval_history``
+/// # Errors
+/// Returns an error if any of the moves, or the engine's best lines, are not legal
+/// Returns an error if the ``FEN`` header can't be parsed
+pub fn annotated_pgn(moves: &[String], eval_history: &EvalHistory, headers: &[(String, String)]) -> Result<String, PgnError> {
+    let (mut board, start_ply) = start_position(headers)?;
+    let mut tokens = Vec::new();
+    let mut force_move_number = true;
+
+    // Drawings on the starting position go before the first move
+    if let Some(record) = eval_history.get(0).filter(|record| !record.drawings.is_empty()) {
+        tokens.push(format!("{{{}}}", pgn_drawings(&record.drawings).join(" ")));
+    }
+
+    for (i, algebraic) in moves.iter().enumerate() {
+        let ply = i + 1;
+        let board_before = board.clone();
+
+        let piece_move = find_legal_move(&board, algebraic)?;
+        let san = to_san(&board, piece_move);
+        board.apply_move(piece_move);
+
+        if let Some(number) = move_number(start_ply + ply, force_move_number) {
+            tokens.push(number);
+        }
+        tokens.push(san);
+        force_move_number = false;
+
+        let Some(record) = eval_history.get(ply) else {
+            continue;
+        };
+
+        if let Some(nag) = record.classification.and_then(|move_class| move_class.to_nag()) {
+            tokens.push(format!("${nag}"));
+        }
+
+        let comment = record
+            .eval
+            .map(|eval| format!("[%eval {}]", pgn_eval(eval)))
+            .into_iter()
+            .chain(pgn_drawings(&record.drawings))
+            .collect::<Vec<_>>();
+
+        if !comment.is_empty() {
+            tokens.push(format!("{{{}}}", comment.join(" ")));
+            force_move_number = true;
+        }
+
+        // Show the line which should have been played instead of a mistake
+        if record.classification.is_some_and(|move_class| move_class.is_mistake()) {
+            let best_line = eval_history
+                .get(ply - 1)
+                .map(|record| &record.best_line[..record.best_line.len().min(MAX_VARIATION_PLIES)])
+                .unwrap_or_default();
+
+            if best_line.first().is_some_and(|best_move| best_move != algebraic) {
+                let mut variation = Vec::new();
+                for (j, san) in line_to_san(&board_before, best_line)?.into_iter().enumerate() {
+                    if let Some(number) = move_number(start_ply + ply + j, j == 0) {
+                        variation.push(number);
+                    }
+                    variation.push(san);
+                }
+
+                tokens.push(format!("({})", variation.join(" ")));
+                force_move_number = true;
+            }
+        }
+    }
+
+    // Prefer the result from the headers, since the game may have ended by resignation
+    let result = headers
+        .iter()
+        .find(|(key, _)| key == "Result")
+        .map_or_else(|| game_result(&board).to_string(), |(_, value)| value.clone());
+    tokens.push(result);
+
+    let mut pgn = String::new();
+    for (key, value) in headers {
+        let _ = writeln!(pgn, "[{key} \"{}\"]", value.replace('"', "'"));
+    }
+    pgn.push('\n');
+
+    // Wrap the movetext so that lines don't get too long
+    let mut line = String::new();
+    for token in tokens {
+        if !line.is_empty() && line.len() + token.len() + 1 > PGN_LINE_WIDTH {
+            pgn.push_str(&line);
+            pgn.push('\n');
+            line.clear();
+        }
+
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(&token);
+    }
+    pgn.push_str(&line);
+    pgn.push_str("\n\n");
+
+    Ok(pgn)
+}
+
+/// The moves of the whole game, including any moves after the current position which can be redone
+#[must_use]
+pub fn game_moves(board: &Board, eval_history: &EvalHistory) -> Vec<String> {
+    let mut moves = board
+        .move_history
+        .to_piece_move_string()
+        .map(|move_history| move_history.split_whitespace().map(ToString::to_string).collect::<Vec<_>>())
+        .unwrap_or_default();
+
+    moves.extend(
+        (moves.len() + 1..eval_history.len())
+            .map_while(|ply| eval_history.get(ply).and_then(|record| record.played_move.clone())),
+    );
+
+    moves
+}
+
+/// Write an annotated PGN of ``moves`` into ``PGN_EXPORT_DIR``, returning the path of the file
+/// # Errors
+/// Returns an error if the moves can't be converted into PGN
+/// Returns an error if the file can't be written
+pub fn export_annotated_pgn(
+    moves: &[String],
+    eval_history: &EvalHistory,
+    extra_headers: &[(String, String)],
+) -> Result<PathBuf, PgnError> {
+    let result = game_result(&moves.iter().try_fold(start_board(), |mut board, algebraic| {
+        board.apply_move(find_legal_move(&board, algebraic)?);
+        Ok::<_, PgnError>(board)
+    })?);
+
+    let mut headers = default_headers(result);
+    headers.extend_from_slice(extra_headers);
+
+    let pgn = annotated_pgn(moves, eval_history, &headers)?;
+
+    fs::create_dir_all(PGN_EXPORT_DIR)?;
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs());
+    let path = PathBuf::from(PGN_EXPORT_DIR).join(format!("game_{timestamp}.pgn"));
+    fs::write(&path, pgn)?;
+
+    Ok(path)
+}
+
+/// A game read from a PGN file, with its moves in long algebraic notation
+#[derive(Debug, Clone, Default)]
+pub struct PgnGame {
+    pub headers: Vec<(String, String)>,
+    pub moves: Vec<String>,
+}
+
+impl PgnGame {
+    #[must_use]
+    pub fn get_header(&self, key: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header_key, _)| header_key == key)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Parse every game in the text of a PGN file, each game which contains an illegal move, or a ``FEN`` header which can't be
+/// parsed, is an error without stopping the rest from being parsed
+#[must_use]
+pub fn parse_pgn(text: &str) -> Vec<Result<PgnGame, PgnError>> {
+    let mut games = Vec::new();
+    let mut headers = Vec::new();
+    let mut movetext = String::new();
+
+    for line in text.lines() {
+        let line = line.trim();
+
+        if let Some(header) = line.strip_prefix('[').and_then(|line| line.strip_suffix(']')) {
+            // A header after some movetext is the start of the next game
+            if !movetext.trim().is_empty() {
+                games.push(parse_movetext(std::mem::take(&mut headers), &std::mem::take(&mut movetext)));
+            }
+
+            if let Some((key, value)) = header.split_once(' ') {
+                headers.push((key.to_string(), value.trim().trim_matches('"').to_string()));
+            }
+        } else if !line.starts_with('%') {
+            movetext.push_str(line);
+            movetext.push('\n');
+        }
+    }
+
+    if !movetext.trim().is_empty() || !headers.is_empty() {
+        games.push(parse_movetext(headers, &movetext));
+    }
+
+    games
+}
+
+/// Convert the moves of a game from SAN into long algebraic notation, skipping comments, variations, NAGs, and move numbers
+fn parse_movetext(headers: Vec<(String, String)>, movetext: &str) -> Result<PgnGame, PgnError> {
+    // Remove comments (Both ``{...}`` and ``;`` until the end of the line) and variations
+    let mut main_line = String::new();
+    let mut in_comment = false;
+    let mut in_line_comment = false;
+    let mut variation_depth = 0_usize;
+    for c in movetext.chars() {
+        match c {
+            '\n' if in_line_comment => {
+                in_line_comment = false;
+                main_line.push(c);
+            }
+            _ if in_line_comment => {}
+            '{' if !in_comment => in_comment = true,
+            '}' if in_comment => in_comment = false,
+            ';' if !in_comment => in_line_comment = true,
+            '(' if !in_comment => variation_depth += 1,
+            ')' if !in_comment => variation_depth = variation_depth.saturating_sub(1),
+            _ if in_comment || variation_depth > 0 => {}
+            _ => main_line.push(c),
+        }
+    }
+
+    let (mut board, _) = start_position(&headers)?;
+    let mut moves = Vec::new();
+
+    for token in main_line.split_whitespace() {
+        // Skip results and NAGs
+        if matches!(token, "1-0" | "0-1" | "1/2-1/2" | "*") || token.starts_with('$') {
+            continue;
+        }
+
+        // Remove any move number which is attached to the move (e.g. "12.e4")
+        let san = if token.starts_with("0-0") {
+            token
+        } else {
+            token.trim_start_matches(|c: char| c.is_ascii_digit() || c == '.')
+        };
+        if san.is_empty() {
+            continue;
+        }
+
+        let piece_move = find_legal_move_from_san(&board, san)?;
+        moves.push(piece_move.to_algebraic().map_err(PgnError::PieceMoveParseError)?);
+        board.apply_move(piece_move);
+    }
+
+    Ok(PgnGame { headers, moves })
+}
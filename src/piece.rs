@@ -3,13 +3,19 @@ use bevy::prelude::*;
 use chess_core::{board::TilePos, piece::Piece, piece_move::PieceMove};
 
 use crate::{
-    display::{board_to_pixel_coords, pixel_to_board_coords, PIECE_SIZE, PIECE_SIZE_IMG},
+    animation::{finish_piece_animation, PieceAnimation},
+    display::{BoardLayout, BoardOrientation, PIECE_SIZE},
     piece_move::PieceMoveEvent,
     possible_moves::PossibleMoveDisplayEvent,
+    selection::PieceSelection,
 };
 
 pub struct PieceBundle;
 
+/// Marks the sprite of a piece, so its texture can be swapped when the theme changes
+#[derive(Component)]
+pub struct PieceSprite;
+
 impl PieceBundle {
     /// # Panics
     /// Panics if ``Piece::None`` used as a bitboard index for the texture atlas
@@ -21,10 +27,12 @@ impl PieceBundle {
         key: Piece,
         texture: Handle<Image>,
         texture_atlas_layout: Handle<TextureAtlasLayout>,
+        scale: f32,
+        orientation: BoardOrientation,
     ) -> EntityCommands<'a> {
         assert!(key != Piece::None, "{key:?} used as bitboard index");
 
-        let (x, y) = board_to_pixel_coords(file, rank);
+        let (x, y) = orientation.board_to_pixel_coords(file, rank);
 
         // Create a bundle with this piece's spritesheet, and Pickable marker
         let mut entity = commands.spawn((
@@ -35,9 +43,10 @@ impl PieceBundle {
                     index: key.to_bitboard_index(), // choose whichever sprite index you want
                 },
             ),
-            Transform::from_scale(Vec3::splat(PIECE_SIZE / PIECE_SIZE_IMG)).with_translation(Vec3::new(x, y, 1.)),
+            Transform::from_scale(Vec3::splat(scale)).with_translation(Vec3::new(x, y, 1.)),
             GlobalTransform::default(),
             Pickable::default(),
+            PieceSprite,
         ));
 
         // Add triggers for drag events
@@ -54,17 +63,39 @@ impl PieceBundle {
 #[allow(clippy::needless_pass_by_value)]
 pub fn on_piece_drag_start(
     ev_drag: Trigger<Pointer<DragStart>>,
+    mut commands: Commands,
     mut ev_draw_moves: EventWriter<PossibleMoveDisplayEvent>,
     mut transform_query: Query<&mut Transform, With<Pickable>>,
+    animation_query: Query<&PieceAnimation>,
+    mut selection: ResMut<PieceSelection>,
+    orientation: Res<BoardOrientation>,
 ) {
     let ev = ev_drag.event();
 
-    let transform = transform_query
+    // Dragging with the right mouse button draws arrows instead
+    if ev.button != PointerButton::Primary {
+        return;
+    }
+
+    let mut transform = transform_query
         .get_mut(ev.target)
         .expect("Dragged entity's transform could not be found");
 
+    // Finish any animation first, so the piece is dragged from its tile and the animation doesn't fight the drag
+    if let Ok(animation) = animation_query.get(ev.target) {
+        finish_piece_animation(&mut commands, &mut transform, ev.target, animation, *orientation);
+    }
+
     let mouse_pos = transform.translation.xy() * Vec2::new(1., -1.);
-    let (file, rank) = pixel_to_board_coords(mouse_pos.x, -mouse_pos.y);
+    let (file, rank) = orientation.pixel_to_board_coords(mouse_pos.x, -mouse_pos.y);
+
+    // Dragging replaces any selection made by clicking
+    if let Some(selected) = selection.tile.take() {
+        ev_draw_moves.write(PossibleMoveDisplayEvent {
+            from: selected,
+            show: false,
+        });
+    }
 
     ev_draw_moves.write(PossibleMoveDisplayEvent {
         from: TilePos::new(file, rank),
@@ -76,18 +107,30 @@ pub fn on_piece_drag_start(
 /// # Panics
 /// Panics if the dragged entity's transform cannot be found
 #[allow(clippy::needless_pass_by_value)]
-pub fn on_piece_drag(ev_drag: Trigger<Pointer<Drag>>, mut transform_query: Query<&mut Transform, With<Pickable>>) {
+pub fn on_piece_drag(
+    ev_drag: Trigger<Pointer<Drag>>,
+    mut transform_query: Query<&mut Transform, With<Pickable>>,
+    layout: Res<BoardLayout>,
+) {
     let ev = ev_drag.event();
 
+    if ev.button != PointerButton::Primary {
+        return;
+    }
+
     let mut transform = transform_query
         .get_mut(ev.target)
         .expect("Dragged entity's transform could not be found");
 
-    transform.translation += Vec3::new(ev.delta.x, -ev.delta.y, 0.);
+    // The mouse moves in logical pixels, which are a different size to board-space units when the board is scaled to fit the window
+    let delta = layout.pixels_to_world(Vec2::new(ev.delta.x, -ev.delta.y));
+    transform.translation += delta.extend(0.);
     transform.translation.z = 10.;
 }
 
 /// Finalise the movement of a piece, either snapping it to the grid, or by moving it back
+///
+/// Dropping the piece off the board cancels the move, and dropping it back on its own tile leaves the click to select it
 /// # Panics
 /// Panics if the dragged entity's transform cannot be found
 #[allow(clippy::needless_pass_by_value)]
@@ -96,31 +139,41 @@ pub fn on_piece_drag_end(
     mut transform_query: Query<&mut Transform, With<Pickable>>,
     mut ev_draw_moves: EventWriter<PossibleMoveDisplayEvent>,
     mut ev_piece_move: EventWriter<PieceMoveEvent>,
+    layout: Res<BoardLayout>,
+    mut selection: ResMut<PieceSelection>,
+    orientation: Res<BoardOrientation>,
 ) {
     let ev = ev_drag.event();
 
+    if ev.button != PointerButton::Primary {
+        return;
+    }
+
     let transform = transform_query
         .get_mut(ev.target)
         .expect("Dragged entity's transform could not be found");
 
     // Find where the piece was moved from in board coordinates
-    let original_pos =
-        transform.translation.xy() - Vec2::new(ev.distance.x, -ev.distance.y) + Vec2::new(PIECE_SIZE, PIECE_SIZE) / 2.;
-    let (ori_file, ori_rank) = pixel_to_board_coords(original_pos.x, original_pos.y);
+    let original_pos = transform.translation.xy() - layout.pixels_to_world(Vec2::new(ev.distance.x, -ev.distance.y))
+        + Vec2::new(PIECE_SIZE, PIECE_SIZE) / 2.;
+    let (ori_file, ori_rank) = orientation.pixel_to_board_coords(original_pos.x, original_pos.y);
 
-    // Find the new position, snapped to board coords, and move the sprite there
-    let (file, rank) = pixel_to_board_coords(
-        transform.translation.x + PIECE_SIZE / 2.,
-        transform.translation.y + PIECE_SIZE / 2.,
-    );
+    let from = TilePos::new(ori_file, ori_rank);
 
-    ev_draw_moves.write(PossibleMoveDisplayEvent {
-        from: TilePos::new(file, rank),
-        show: false,
-    });
+    // Find the new position snapped to board coords, which is None if the piece was dropped off the board
+    let dropped_tile = orientation
+        .try_pixel_to_board_coords(transform.translation.x, transform.translation.y)
+        .map(TilePos::from);
+    let to = dropped_tile.unwrap_or(from);
+
+    ev_draw_moves.write(PossibleMoveDisplayEvent { from: to, show: false });
+
+    // The release which ended this drag also clicks the piece, which should only select it if it was dropped back on its tile
+    selection.ignore_click = dropped_tile != Some(from);
 
+    // A move to the same tile is rejected, which slides the piece back
     ev_piece_move.write(PieceMoveEvent {
-        piece_move: PieceMove::new(TilePos::new(ori_file, ori_rank), TilePos::new(file, rank)),
+        piece_move: PieceMove::new(from, to),
         entity: ev.target,
     });
 }
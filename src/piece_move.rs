@@ -1,12 +1,19 @@
 use bevy::prelude::*;
 
-use chess_core::piece_move::PieceMove;
+use chess_core::{
+    piece::Piece,
+    piece_move::{PieceMove, PieceMoveType},
+};
 
 use crate::{
+    animation::animate_piece_entity,
     board::BoardBevy,
-    display::{translate_piece_entity, BackgroundColourEvent},
+    display::{BackgroundColourEvent, BoardOrientation},
+    eval_graph::EvalHistory,
     game_end::GameEndEvent,
     last_move::LastMoveEvent,
+    premove::{is_human_piece, PremoveQueue},
+    promotion::{AutoQueen, PromotionPickerEvent},
     uci::{transmit_to_uci, UciMessage, ENGINE_PLAYER},
 };
 
@@ -20,6 +27,7 @@ pub struct PieceMoveEvent {
 /// Panics if the move history can't be converted to a string to send to via uci to the engine
 /// Panics if message cannot be sent via uci
 #[allow(clippy::too_many_arguments)]
+#[allow(clippy::needless_pass_by_value)]
 pub fn piece_move_event_handler(
     mut commands: Commands,
     mut ev_piece_move: EventReader<PieceMoveEvent>,
@@ -29,16 +37,75 @@ pub fn piece_move_event_handler(
     mut background_ev: EventWriter<BackgroundColourEvent>,
     mut game_end_ev: EventWriter<GameEndEvent>,
     mut last_move_ev: EventWriter<LastMoveEvent>,
+    mut eval_history: ResMut<EvalHistory>,
+    mut ev_promotion_picker: EventWriter<PromotionPickerEvent>,
+    mut premove_queue: ResMut<PremoveQueue>,
+    orientation: Res<BoardOrientation>,
+    auto_queen: Res<AutoQueen>,
 ) {
     for ev in ev_piece_move.read() {
-        let piece_move = ev.piece_move;
+        // Moving the human's pieces while the engine is thinking queues a premove, which is checked for legality once the engine has moved
+        if board.board.get_player() == ENGINE_PLAYER
+            && is_human_piece(board.board.get_piece(ev.piece_move.from))
+            && ev.piece_move.from != ev.piece_move.to
+        {
+            premove_queue.moves.push_back(ev.piece_move);
+            animate_piece_entity(
+                &mut commands,
+                &mut transform_query,
+                ev.entity,
+                ev.piece_move.from,
+                *orientation,
+            );
+            continue;
+        }
+
+        let candidates = board
+            .board
+            .get_possible_moves(ev.piece_move.from)
+            .into_iter()
+            .filter(|candidate| candidate.to == ev.piece_move.to)
+            .collect::<Vec<_>>();
+
+        let is_promotion = candidates
+            .iter()
+            .any(|candidate| matches!(candidate.move_type, PieceMoveType::Promotion(_)));
+
+        // Promotions have a candidate for each piece, so ask which one to promote to unless it has already been picked
+        let promote_to = match ev.piece_move.move_type {
+            PieceMoveType::Promotion(promoted_to) => Some(promoted_to),
+            _ if is_promotion && auto_queen.0 => Some(Piece::get_player_piece(board.board.get_player(), Piece::WQueen)),
+            _ if is_promotion && board.board.get_player() != ENGINE_PLAYER => {
+                // Leave the pawn where it was until a piece is picked
+                animate_piece_entity(
+                    &mut commands,
+                    &mut transform_query,
+                    ev.entity,
+                    ev.piece_move.from,
+                    *orientation,
+                );
+                ev_promotion_picker.write(PromotionPickerEvent {
+                    piece_move: ev.piece_move,
+                    entity: ev.entity,
+                });
+                continue;
+            }
+            _ => None,
+        };
+
+        let legal_move = candidates
+            .into_iter()
+            .find(|candidate| promote_to.is_none_or(|promoted_to| candidate.move_type == PieceMoveType::Promotion(promoted_to)));
+
+        // An illegal move keeps the requested tiles, so the piece is slid back below
+        let piece_move = legal_move.unwrap_or(ev.piece_move);
 
         // Snap the moved entity to the grid (Don't move if there is a non-opponent piece there, or if you moved a piece on another player's turn, or if the move is impossible for that piece type)
 
         // TODO ENGINE_PLAYER can't be white
         if !board.board.get_piece(piece_move.to).is_player(board.board.player)
             && board.board.get_piece(piece_move.from).is_player(board.board.player)
-            && board.board.get_possible_moves(piece_move.from).contains(&piece_move)
+            && legal_move.is_some()
             && board.board.get_player() != ENGINE_PLAYER
         {
             // Apply the move to the board
@@ -50,10 +117,19 @@ pub fn piece_move_event_handler(
                     &mut background_ev,
                     &mut game_end_ev,
                     &mut last_move_ev,
+                    *orientation,
                     piece_move,
                 )
                 .is_some()
             {
+                // Any evals stored for plies after this move belong to a different line
+                let ply = board.current_ply();
+                eval_history.truncate(ply);
+
+                if let Ok(played_move) = piece_move.to_algebraic() {
+                    eval_history.set_played_move(ply, played_move);
+                }
+
                 // Send the moves to the chess engine, if the game hasn't ended
                 transmit_to_uci(UciMessage::NewMove {
                     move_history: board
@@ -66,8 +142,8 @@ pub fn piece_move_event_handler(
                 .unwrap_or_else(|e| panic!("{e}"));
             }
         } else {
-            // Reset position
-            translate_piece_entity(&mut transform_query, ev.entity, piece_move.from);
+            // Slide the piece back to where it was dragged from
+            animate_piece_entity(&mut commands, &mut transform_query, ev.entity, piece_move.from, *orientation);
         }
     }
 }
@@ -1,14 +1,14 @@
 use bevy::prelude::*;
 
-use chess_core::board::TilePos;
+use chess_core::{board::TilePos, piece::Piece, piece_move::PieceMoveType};
 
 use crate::{
+    arrow::{spawn_tile_dot, spawn_tile_ring},
     board::BoardBevy,
-    display::{board_to_pixel_coords, PIECE_SIZE},
+    display::BoardOrientation,
+    theme::ThemeState,
 };
 
-const POSSIBLE_MOVE_COLOUR: Color = Color::linear_rgba(0., 1., 0., 0.75);
-
 #[derive(Event, Debug)]
 pub struct PossibleMoveDisplayEvent {
     pub from: TilePos,
@@ -19,25 +19,50 @@ pub struct PossibleMoveDisplayEvent {
 pub struct PossibleMoveMarker;
 
 #[allow(clippy::needless_pass_by_value)]
+#[allow(clippy::too_many_arguments)]
 pub fn possible_move_event_handler(
     mut ev_display: EventReader<PossibleMoveDisplayEvent>,
     possible_move_entities: Query<Entity, With<PossibleMoveMarker>>,
     mut commands: Commands,
     board: ResMut<BoardBevy>,
+    theme_state: Res<ThemeState>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    orientation: Res<BoardOrientation>,
 ) {
     for ev in ev_display.read() {
         if ev.show {
-            for pos in board.board.get_possible_moves(ev.from) {
-                let (x, y) = board_to_pixel_coords(pos.to.file, pos.to.rank);
-
-                commands.spawn((
-                    Sprite {
-                        color: POSSIBLE_MOVE_COLOUR,
-                        ..default()
-                    },
-                    Transform::from_xyz(x, y, 2.).with_scale(Vec3::splat(PIECE_SIZE * 0.75)),
-                    PossibleMoveMarker,
-                ));
+            let colour = theme_state.theme().possible_move.0;
+            let mut targets = board.board.get_possible_moves(ev.from);
+
+            // Promotions have a move for each piece, but only one marker is needed
+            targets.dedup_by_key(|pos| pos.to);
+
+            for pos in targets {
+                // Captures are ringed, and quiet moves are marked with a dot
+                let is_capture = board.board.get_piece(pos.to) != Piece::None || pos.move_type == PieceMoveType::EnPassant;
+
+                if is_capture {
+                    spawn_tile_ring(
+                        &mut commands,
+                        &mut meshes,
+                        &mut materials,
+                        pos.to,
+                        colour,
+                        PossibleMoveMarker,
+                        *orientation,
+                    );
+                } else {
+                    spawn_tile_dot(
+                        &mut commands,
+                        &mut meshes,
+                        &mut materials,
+                        pos.to,
+                        colour,
+                        PossibleMoveMarker,
+                        *orientation,
+                    );
+                }
             }
         } else {
             // Stop displaying all entities
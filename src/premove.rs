@@ -0,0 +1,120 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use chess_core::{
+    board::TilePos,
+    piece::Piece,
+    piece_move::{PieceMove, PieceMoveType},
+};
+
+use crate::{
+    board::BoardBevy,
+    display::{BoardOrientation, PIECE_SIZE},
+    piece_move::PieceMoveEvent,
+    theme::ThemeState,
+    uci::ENGINE_PLAYER,
+    uci_event::{UciEvent, UciToBoardMessage},
+};
+
+/// Moves made by the human while the engine is thinking, which are played in order as the engine replies
+#[derive(Resource, Debug, Clone, Default)]
+pub struct PremoveQueue {
+    pub moves: VecDeque<PieceMove>,
+}
+
+#[derive(Component)]
+pub struct PremoveMarker;
+
+/// Whether ``piece`` belongs to the human, who can premove it while the engine is thinking
+#[must_use]
+pub fn is_human_piece(piece: Piece) -> bool {
+    piece.to_player().is_some_and(|player| player != ENGINE_PLAYER)
+}
+
+/// Play the next premove once the engine has moved, discarding it if it is no longer legal
+#[allow(clippy::needless_pass_by_value)]
+pub fn premove_handler(
+    mut ev_uci_to_board: EventReader<UciEvent>,
+    board: Res<BoardBevy>,
+    mut premove_queue: ResMut<PremoveQueue>,
+    mut ev_piece_move: EventWriter<PieceMoveEvent>,
+) {
+    for ev in ev_uci_to_board.read() {
+        if !matches!(ev.message(), UciToBoardMessage::BestMove(_)) || board.board.get_player() == ENGINE_PLAYER {
+            continue;
+        }
+
+        let Some(premove) = premove_queue.moves.pop_front() else {
+            continue;
+        };
+
+        // Premoves to the last rank always promote to a queen, as there was no way to pick a piece
+        let queen = Piece::get_player_piece(board.board.get_player(), Piece::WQueen);
+        let legal_move = board
+            .board
+            .get_possible_moves(premove.from)
+            .into_iter()
+            .filter(|candidate| candidate.to == premove.to)
+            .find(|candidate| match candidate.move_type {
+                PieceMoveType::Promotion(promoted_to) => promoted_to == queen,
+                _ => true,
+            });
+
+        // Premoves which are no longer legal are dropped
+        if let (Some(piece_move), Some(entity)) = (legal_move, board.get_entity(premove.from)) {
+            if board.board.get_piece(premove.from).is_player(board.board.get_player()) {
+                ev_piece_move.write(PieceMoveEvent { piece_move, entity });
+            }
+        }
+    }
+}
+
+/// Clear every premove with a right click
+#[allow(clippy::needless_pass_by_value)]
+pub fn clear_premoves_handler(mouse_buttons: Res<ButtonInput<MouseButton>>, mut premove_queue: ResMut<PremoveQueue>) {
+    if mouse_buttons.just_pressed(MouseButton::Right) && !premove_queue.moves.is_empty() {
+        premove_queue.moves.clear();
+    }
+}
+
+/// Highlight the tiles of every queued premove
+#[allow(clippy::needless_pass_by_value)]
+pub fn update_premove_markers(
+    mut commands: Commands,
+    board: Res<BoardBevy>,
+    premove_queue: Res<PremoveQueue>,
+    theme_state: Res<ThemeState>,
+    marker_entities: Query<Entity, With<PremoveMarker>>,
+    orientation: Res<BoardOrientation>,
+) {
+    // Redraw when the board is flipped or the theme is changed too
+    if !premove_queue.is_changed() && !board.is_changed() && !theme_state.is_changed() && !orientation.is_changed() {
+        return;
+    }
+
+    for entity in marker_entities.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    let mut tiles = premove_queue
+        .moves
+        .iter()
+        .flat_map(|premove| [premove.from, premove.to])
+        .collect::<Vec<TilePos>>();
+    tiles.sort_by_key(|tile| (tile.file, tile.rank));
+    tiles.dedup();
+
+    for tile in tiles {
+        let (x, y) = orientation.board_to_pixel_coords(tile.file, tile.rank);
+
+        commands.spawn((
+            Sprite {
+                color: theme_state.theme().premove.0,
+                ..default()
+            },
+            Transform::from_xyz(x, y, 0.55).with_scale(Vec3::splat(PIECE_SIZE)),
+            PremoveMarker,
+        ));
+    }
+}
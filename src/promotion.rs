@@ -0,0 +1,180 @@
+use bevy::prelude::*;
+
+use chess_core::{
+    board::TilePos,
+    piece::Piece,
+    piece_move::{PieceMove, PieceMoveType},
+};
+
+use crate::{
+    board::BoardBevy,
+    display::{get_piece_texture_atlas, BoardOrientation, PIECE_SIZE},
+    piece_move::PieceMoveEvent,
+    selection::PieceSelection,
+    theme::ThemeState,
+};
+
+/// The pieces offered by the picker, from the promotion tile towards the centre of the board
+const PROMOTION_PIECES: [Piece; 4] = [Piece::WQueen, Piece::WKnight, Piece::WRook, Piece::WBishop];
+const PICKER_COLOUR: Color = Color::linear_rgba(0.9, 0.9, 0.9, 0.95);
+/// Depth of the picker, above the pieces and any markers
+const PICKER_Z: f32 = 20.;
+
+/// Whether promotions skip the picker and always promote to a queen, set by the ``--auto-queen`` argument
+#[derive(Resource, Debug, Clone, Copy, Default)]
+pub struct AutoQueen(pub bool);
+
+/// Ask the human which piece to promote to, for a pawn moving from ``piece_move.from`` to ``piece_move.to``
+#[derive(Event)]
+pub struct PromotionPickerEvent {
+    pub piece_move: PieceMove,
+    pub entity: Entity,
+}
+
+/// The promotion waiting for a piece to be picked
+#[derive(Resource, Debug, Clone, Default)]
+pub struct PromotionState {
+    pub pending: Option<(PieceMove, Entity)>,
+}
+
+/// Part of the picker, so it can be despawned once a piece is picked
+#[derive(Component)]
+pub struct PromotionPickerMarker;
+
+/// A piece which can be picked, drawn on ``tile``
+#[derive(Component)]
+pub struct PromotionOption {
+    pub piece: Piece,
+    pub tile: TilePos,
+}
+
+/// Show the picker on the promotion tile, with the other options stacked towards the centre of the board
+/// # Panics
+/// Panics if the promoting piece's player can't be found
+#[allow(clippy::needless_pass_by_value)]
+#[allow(clippy::too_many_arguments)]
+pub fn promotion_picker_event_handler(
+    mut ev_promotion_picker: EventReader<PromotionPickerEvent>,
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+    board: Res<BoardBevy>,
+    theme_state: Res<ThemeState>,
+    mut promotion_state: ResMut<PromotionState>,
+    picker_entities: Query<Entity, With<PromotionPickerMarker>>,
+    orientation: Res<BoardOrientation>,
+) {
+    for ev in ev_promotion_picker.read() {
+        // Only one picker can be shown at a time
+        for entity in picker_entities.iter() {
+            commands.entity(entity).despawn();
+        }
+
+        let player = board
+            .board
+            .get_piece(ev.piece_move.from)
+            .to_player()
+            .expect("Promoting piece has no player");
+
+        // Stack downwards from White's last rank, and upwards from Black's
+        let towards_centre = |i: u32| {
+            if ev.piece_move.to.rank == 0 {
+                ev.piece_move.to.rank + i
+            } else {
+                ev.piece_move.to.rank - i
+            }
+        };
+
+        let theme = theme_state.theme();
+        let (texture, texture_atlas_layout) = get_piece_texture_atlas(&asset_server, &mut texture_atlas_layouts, theme);
+
+        for (i, piece) in (0..).zip(PROMOTION_PIECES) {
+            let tile = TilePos::new(ev.piece_move.to.file, towards_centre(i));
+            let piece = Piece::get_player_piece(player, piece);
+            let (x, y) = orientation.board_to_pixel_coords(tile.file, tile.rank);
+
+            commands.spawn((
+                Sprite {
+                    color: PICKER_COLOUR,
+                    custom_size: Some(Vec2::new(PIECE_SIZE, PIECE_SIZE)),
+                    ..default()
+                },
+                Transform::from_xyz(x, y, PICKER_Z),
+                PromotionPickerMarker,
+            ));
+
+            commands.spawn((
+                Sprite::from_atlas_image(
+                    texture.clone(),
+                    TextureAtlas {
+                        layout: texture_atlas_layout.clone(),
+                        index: piece.to_bitboard_index(),
+                    },
+                ),
+                Transform::from_scale(Vec3::splat(theme.piece_scale())).with_translation(Vec3::new(x, y, PICKER_Z + 0.1)),
+                PromotionPickerMarker,
+                PromotionOption { piece, tile },
+            ));
+        }
+
+        promotion_state.pending = Some((ev.piece_move, ev.entity));
+    }
+}
+
+/// Promote to the piece which was clicked, clicking anywhere else (Or the position changing) cancels the promotion
+#[allow(clippy::needless_pass_by_value)]
+#[allow(clippy::too_many_arguments)]
+pub fn promotion_picker_click_handler(
+    mut ev_click: EventReader<Pointer<Click>>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    mut commands: Commands,
+    board: Res<BoardBevy>,
+    mut promotion_state: ResMut<PromotionState>,
+    mut selection: ResMut<PieceSelection>,
+    mut ev_piece_move: EventWriter<PieceMoveEvent>,
+    option_query: Query<&PromotionOption>,
+    picker_entities: Query<Entity, With<PromotionPickerMarker>>,
+    orientation: Res<BoardOrientation>,
+) {
+    let clicked_tile = ev_click
+        .read()
+        .filter(|ev| ev.button == PointerButton::Primary)
+        .last()
+        .map(|ev| {
+            ev.hit
+                .position
+                .and_then(|position| orientation.try_pixel_to_board_coords(position.x, position.y))
+        });
+
+    // The picker is spawned after the click which made the move, so that click isn't counted
+    if option_query.is_empty() {
+        return;
+    }
+
+    let tile = match clicked_tile {
+        Some(tile) => tile.map(TilePos::from),
+        None if mouse_buttons.just_released(MouseButton::Left) => None,
+        None if board.is_changed() || orientation.is_changed() => None,
+        None => return,
+    };
+
+    let picked = tile.and_then(|tile| option_query.iter().find(|option| option.tile == tile));
+
+    if let (Some(option), Some((piece_move, entity))) = (picked, promotion_state.pending) {
+        ev_piece_move.write(PieceMoveEvent {
+            piece_move: PieceMove {
+                move_type: PieceMoveType::Promotion(option.piece),
+                ..piece_move
+            },
+            entity,
+        });
+    }
+
+    // The click was for the picker, so it shouldn't select a piece too
+    selection.ignore_click = clicked_tile.is_some();
+    promotion_state.pending = None;
+
+    for entity in picker_entities.iter() {
+        commands.entity(entity).despawn();
+    }
+}
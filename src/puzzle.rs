@@ -0,0 +1,172 @@
+use std::{
+    collections::HashSet,
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+};
+
+use thiserror::Error;
+
+use crate::{
+    board::player_to_move_at_ply,
+    classification::MoveClassification,
+    eval_cache::{board_after_moves, fen_key},
+    eval_graph::EvalHistory,
+    uci::UciError,
+    uci_info::UciEval,
+};
+
+pub const PUZZLE_FILE: &str = "puzzles.csv";
+const PUZZLE_FILE_HEADER: &str = "PuzzleId,FEN,Moves,Themes";
+
+/// The eval (For the player to move) which counts as a decisive advantage
+const DECISIVE_CENTIPAWNS: i32 = 300;
+const CRUSHING_CENTIPAWNS: i32 = 600;
+/// Non-mating solutions are cut off after this many plies
+const MAX_SOLUTION_PLIES: usize = 6;
+
+#[derive(Error, Debug)]
+pub enum PuzzleError {
+    #[error("Puzzle file could not be read/wrote to:\n\t{0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error(transparent)]
+    UciError(#[from] UciError),
+}
+
+#[derive(Debug, Clone)]
+pub struct Puzzle {
+    pub fen: String,
+    pub solution: Vec<String>,
+    pub themes: Vec<String>,
+}
+
+impl Puzzle {
+    /// Create a puzzle for the position at ``ply``, if the move which reached it was a blunder (Or a miss) which gave the player to move a decisive reply
+    /// # Errors
+    /// Returns an error if the moves leading to ``ply`` cannot be replayed
+    pub fn from_ply(eval_history: &EvalHistory, ply: usize) -> Result<Option<Self>, PuzzleError> {
+        let Some(record) = eval_history.get(ply) else {
+            return Ok(None);
+        };
+
+        if !matches!(
+            record.classification,
+            Some(MoveClassification::Blunder | MoveClassification::Miss)
+        ) {
+            return Ok(None);
+        }
+
+        let Some(eval) = record.eval else {
+            return Ok(None);
+        };
+
+        if record.best_line.is_empty() {
+            return Ok(None);
+        }
+
+        // Flip the eval so that it is from the perspective of the player who is punishing the blunder
+        let eval = eval.to_white_perspective(player_to_move_at_ply(ply));
+
+        let (mut themes, solution_plies) = match eval {
+            UciEval::Mate(mate_in) if mate_in > 0 => (
+                vec![String::from("mate"), format!("mateIn{mate_in}")],
+                (mate_in.unsigned_abs() as usize * 2 - 1).min(record.best_line.len()),
+            ),
+            UciEval::Centipawn(eval) if eval >= DECISIVE_CENTIPAWNS => (
+                vec![String::from(if eval >= CRUSHING_CENTIPAWNS {
+                    "crushing"
+                } else {
+                    "advantage"
+                })],
+                MAX_SOLUTION_PLIES.min(record.best_line.len()),
+            ),
+            _ => return Ok(None),
+        };
+
+        // Get the moves which reached this position
+        let Some(move_history) = (1..=ply)
+            .map(|ply| eval_history.get(ply).and_then(|record| record.played_move.clone()))
+            .collect::<Option<Vec<_>>>()
+        else {
+            return Ok(None);
+        };
+
+        let solution = record.best_line[..solution_plies].to_vec();
+
+        themes.push(String::from(match solution.len() {
+            1 => "oneMove",
+            2..=3 => "short",
+            4..=5 => "long",
+            _ => "veryLong",
+        }));
+
+        Ok(Some(Self {
+            fen: board_after_moves(&move_history.join(" "))?.to_fen(),
+            solution,
+            themes,
+        }))
+    }
+
+    /// Key the puzzle by its position, so the same position isn't saved twice
+    #[must_use]
+    pub fn position_key(&self) -> String {
+        fen_key(&self.fen)
+    }
+}
+
+/// Find every puzzle in a reviewed game
+/// # Errors
+/// Returns an error if the moves leading to a puzzle cannot be replayed
+pub fn find_puzzles(eval_history: &EvalHistory) -> Result<Vec<Puzzle>, PuzzleError> {
+    (1..eval_history.len())
+        .filter_map(|ply| Puzzle::from_ply(eval_history, ply).transpose())
+        .collect()
+}
+
+/// Append any new puzzles from a reviewed game to ``PUZZLE_FILE``, returning how many were added
+/// # Errors
+/// Returns an error if the puzzle file cannot be read or wrote to
+/// Returns an error if the moves leading to a puzzle cannot be replayed
+pub fn export_puzzles(eval_history: &EvalHistory) -> Result<usize, PuzzleError> {
+    // Read the positions which are already in the collection
+    let mut existing_keys = HashSet::new();
+    let mut puzzle_amt = 0;
+    if let Ok(file) = File::open(PUZZLE_FILE) {
+        for line in BufReader::new(file).lines().skip(1) {
+            let line = line?;
+
+            if let Some(fen) = line.split(',').nth(1) {
+                existing_keys.insert(fen_key(fen));
+                puzzle_amt += 1;
+            }
+        }
+    }
+
+    let new_puzzles = find_puzzles(eval_history)?
+        .into_iter()
+        .filter(|puzzle| existing_keys.insert(puzzle.position_key()))
+        .collect::<Vec<_>>();
+
+    if new_puzzles.is_empty() {
+        return Ok(0);
+    }
+
+    let mut file = OpenOptions::new().create(true).append(true).open(PUZZLE_FILE)?;
+
+    if puzzle_amt == 0 && file.metadata()?.len() == 0 {
+        writeln!(file, "{PUZZLE_FILE_HEADER}")?;
+    }
+
+    for (i, puzzle) in new_puzzles.iter().enumerate() {
+        writeln!(
+            file,
+            "{:05},{},{},{}",
+            puzzle_amt + i + 1,
+            puzzle.fen,
+            puzzle.solution.join(" "),
+            puzzle.themes.join(" ")
+        )?;
+    }
+
+    Ok(new_puzzles.len())
+}
@@ -0,0 +1,131 @@
+use bevy::prelude::*;
+
+use chess_core::{board::TilePos, piece_move::PieceMove};
+
+use crate::{
+    board::BoardBevy,
+    display::{BoardOrientation, PIECE_SIZE},
+    piece_move::PieceMoveEvent,
+    possible_moves::PossibleMoveDisplayEvent,
+    premove::is_human_piece,
+    theme::ThemeState,
+};
+
+/// The piece selected by clicking it, which is moved by clicking one of its legal target tiles
+#[derive(Resource, Debug, Clone, Default)]
+pub struct PieceSelection {
+    pub tile: Option<TilePos>,
+    /// Set when a drag has just moved a piece, as releasing the mouse after a drag also sends a click
+    pub ignore_click: bool,
+}
+
+#[derive(Component)]
+pub struct SelectionMarker;
+
+/// Select a piece by clicking it, then move it by clicking a target tile, clicking anywhere else cancels the selection
+/// # Panics
+/// Panics if the selected piece's entity can't be found
+#[allow(clippy::needless_pass_by_value)]
+pub fn click_to_move_handler(
+    mut ev_click: EventReader<Pointer<Click>>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    board: Res<BoardBevy>,
+    mut selection: ResMut<PieceSelection>,
+    mut ev_draw_moves: EventWriter<PossibleMoveDisplayEvent>,
+    mut ev_piece_move: EventWriter<PieceMoveEvent>,
+    orientation: Res<BoardOrientation>,
+) {
+    // The selection is stale once the position has changed (e.g. by the engine moving, or the history being navigated)
+    if board.is_changed() && selection.tile.is_some() {
+        clear_selection(&mut selection, &mut ev_draw_moves);
+    }
+
+    // Every hovered entity is sent a click, but only the tile clicked matters
+    let clicked_tile = ev_click
+        .read()
+        .filter(|ev| ev.button == PointerButton::Primary)
+        .last()
+        .map(|ev| {
+            ev.hit
+                .position
+                .and_then(|position| orientation.try_pixel_to_board_coords(position.x, position.y))
+        });
+
+    if selection.ignore_click {
+        selection.ignore_click = false;
+        return;
+    }
+
+    let tile = match clicked_tile {
+        Some(tile) => tile.map(TilePos::from),
+        // Clicking outside of any entity, such as the window's margin
+        None if mouse_buttons.just_released(MouseButton::Left) => None,
+        None => return,
+    };
+
+    // Pieces can be selected during the engine's turn too, to premove them
+    let is_own_piece = |tile: TilePos| is_human_piece(board.board.get_piece(tile));
+
+    match (selection.tile, tile) {
+        // Clicking the selected piece again deselects it
+        (Some(selected), Some(tile)) if selected == tile => clear_selection(&mut selection, &mut ev_draw_moves),
+        (_, Some(tile)) if is_own_piece(tile) => {
+            clear_selection(&mut selection, &mut ev_draw_moves);
+
+            selection.tile = Some(tile);
+            ev_draw_moves.write(PossibleMoveDisplayEvent { from: tile, show: true });
+        }
+        (Some(selected), Some(tile)) => {
+            clear_selection(&mut selection, &mut ev_draw_moves);
+
+            // Illegal moves are rejected by the move handler, which leaves the piece where it is
+            let entity = board
+                .get_entity(selected)
+                .unwrap_or_else(|| panic!("Entity not found for selected piece at pos {selected}"));
+
+            ev_piece_move.write(PieceMoveEvent {
+                piece_move: PieceMove::new(selected, tile),
+                entity,
+            });
+        }
+        _ => clear_selection(&mut selection, &mut ev_draw_moves),
+    }
+}
+
+fn clear_selection(selection: &mut PieceSelection, ev_draw_moves: &mut EventWriter<PossibleMoveDisplayEvent>) {
+    if let Some(tile) = selection.tile.take() {
+        ev_draw_moves.write(PossibleMoveDisplayEvent { from: tile, show: false });
+    }
+}
+
+/// Highlight the selected piece's tile
+#[allow(clippy::needless_pass_by_value)]
+pub fn update_selection_marker(
+    mut commands: Commands,
+    selection: Res<PieceSelection>,
+    theme_state: Res<ThemeState>,
+    marker_entities: Query<Entity, With<SelectionMarker>>,
+    orientation: Res<BoardOrientation>,
+) {
+    // Redraw when the board is flipped too
+    if !selection.is_changed() && !orientation.is_changed() {
+        return;
+    }
+
+    for entity in marker_entities.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    if let Some(tile) = selection.tile {
+        let (x, y) = orientation.board_to_pixel_coords(tile.file, tile.rank);
+
+        commands.spawn((
+            Sprite {
+                color: theme_state.theme().selected.0,
+                ..default()
+            },
+            Transform::from_xyz(x, y, 0.6).with_scale(Vec3::splat(PIECE_SIZE)),
+            SelectionMarker,
+        ));
+    }
+}
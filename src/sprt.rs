@@ -0,0 +1,509 @@
+use std::{
+    fs::{self, File},
+    io::Write,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use thiserror::Error;
+
+use chess_core::board::Player;
+
+use crate::{
+    tournament::{play_game, tournament_game_pgn, GameRecord, GameResult, Pairing, TournamentConfig, TournamentError},
+    uci::UciEngine,
+};
+
+/// Index of the engine being tested, and the engine it is compared against, in the tournament config
+const DEV_ENGINE: usize = 0;
+const BASE_ENGINE: usize = 1;
+
+/// Z score of a 95% confidence interval
+const CONFIDENCE_Z: f64 = 1.96;
+
+#[derive(Error, Debug)]
+pub enum SprtError {
+    #[error(
+        "Usage: chess_gui sprt --engine <dev name>=<command> --engine <base name>=<command> [--elo0 <elo>] [--elo1 <elo>] [--alpha <alpha>] [--beta <beta>] [--concurrency <engine pairs>] [--max-games <games>] [Any tournament options]\n\t{0}"
+    )]
+    UsageError(String),
+
+    #[error(transparent)]
+    TournamentError(#[from] TournamentError),
+
+    #[error("SPRT files could not be read/wrote to:\n\t{0}")]
+    IoError(#[from] std::io::Error),
+}
+
+/// The hypotheses of the test, and the error rates which are accepted
+#[derive(Debug, Clone, Copy)]
+pub struct SprtConfig {
+    /// Elo difference of the null hypothesis (The dev engine is no better)
+    pub elo0: f64,
+    /// Elo difference of the alternative hypothesis (The dev engine is better)
+    pub elo1: f64,
+    /// Chance of accepting the alternative hypothesis when the null hypothesis is true
+    pub alpha: f64,
+    /// Chance of accepting the null hypothesis when the alternative hypothesis is true
+    pub beta: f64,
+}
+
+impl Default for SprtConfig {
+    fn default() -> Self {
+        Self {
+            elo0: 0.,
+            elo1: 5.,
+            alpha: 0.05,
+            beta: 0.05,
+        }
+    }
+}
+
+impl SprtConfig {
+    /// The LLR below which the null hypothesis is accepted
+    #[must_use]
+    pub fn lower_bound(&self) -> f64 {
+        (self.beta / (1. - self.alpha)).ln()
+    }
+
+    /// The LLR above which the alternative hypothesis is accepted
+    #[must_use]
+    pub fn upper_bound(&self) -> f64 {
+        ((1. - self.beta) / self.alpha).ln()
+    }
+}
+
+/// Results of the games from the dev engine's perspective
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SprtResults {
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+}
+
+impl SprtResults {
+    pub fn add(&mut self, record: &GameRecord) {
+        let dev_player = if record.pairing.white == DEV_ENGINE {
+            Player::White
+        } else {
+            Player::Black
+        };
+
+        match record.result {
+            GameResult::Win(winner) if winner == dev_player => self.wins += 1,
+            GameResult::Win(_) => self.losses += 1,
+            GameResult::Draw => self.draws += 1,
+        }
+    }
+
+    #[must_use]
+    pub const fn games(&self) -> u32 {
+        self.wins + self.draws + self.losses
+    }
+
+    /// The mean score per game, and the variance of the score of a single game
+    fn score_and_variance(&self) -> Option<(f64, f64)> {
+        let games = f64::from(self.games());
+        if self.games() == 0 {
+            return None;
+        }
+
+        let win_rate = f64::from(self.wins) / games;
+        let draw_rate = f64::from(self.draws) / games;
+
+        let score = win_rate + draw_rate / 2.;
+        let variance = (win_rate + draw_rate / 4.) - score * score;
+
+        Some((score, variance))
+    }
+
+    /// The log likelihood ratio of the alternative hypothesis against the null hypothesis (Using the trinomial GSPRT approximation)
+    #[must_use]
+    pub fn llr(&self, config: &SprtConfig) -> f64 {
+        let Some((score, variance)) = self.score_and_variance() else {
+            return 0.;
+        };
+
+        // Every game had the same score (e.g. all draws), so there isn't enough information to estimate the variance
+        if variance <= 0. {
+            return 0.;
+        }
+
+        let variance_of_mean = variance / f64::from(self.games());
+
+        let score0 = elo_to_score(config.elo0);
+        let score1 = elo_to_score(config.elo1);
+
+        (score1 - score0) * 2_f64.mul_add(score, -score0 - score1) / (2. * variance_of_mean)
+    }
+
+    /// The Elo difference of the dev engine over the base engine, with the error of a 95% confidence interval
+    #[must_use]
+    pub fn elo(&self) -> Option<(f64, f64)> {
+        let (score, variance) = self.score_and_variance()?;
+        let margin = CONFIDENCE_Z * (variance / f64::from(self.games())).sqrt();
+
+        let elo = score_to_elo(score);
+        let error = (score_to_elo(score + margin) - score_to_elo(score - margin)) / 2.;
+
+        Some((elo, error))
+    }
+}
+
+/// The expected score of an engine which is ``elo`` stronger than its opponent
+#[must_use]
+pub fn elo_to_score(elo: f64) -> f64 {
+    1. / (1. + 10_f64.powf(-elo / 400.))
+}
+
+/// The Elo difference which gives an expected score of ``score`` (Clamped so that perfect scores are finite)
+#[must_use]
+pub fn score_to_elo(score: f64) -> f64 {
+    let score = score.clamp(1e-6, 1. - 1e-6);
+
+    -400. * (1. / score - 1.).log10()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SprtVerdict {
+    /// The dev engine is stronger by at least ``elo1``
+    AcceptH1,
+    /// The dev engine isn't stronger than ``elo0``
+    AcceptH0,
+}
+
+/// Decide whether the test has finished, given the current LLR
+#[must_use]
+pub fn sprt_verdict(llr: f64, config: &SprtConfig) -> Option<SprtVerdict> {
+    if llr >= config.upper_bound() {
+        Some(SprtVerdict::AcceptH1)
+    } else if llr <= config.lower_bound() {
+        Some(SprtVerdict::AcceptH0)
+    } else {
+        None
+    }
+}
+
+/// Describe the state of the test in one line (e.g. ``Games: 120  W: 40  D: 50  L: 30  Elo: 29.0 +/- 48.1  LLR: 0.52 [-2.94, 2.94]``)
+#[must_use]
+pub fn describe_results(results: &SprtResults, config: &SprtConfig) -> String {
+    let elo = results
+        .elo()
+        .map_or_else(|| String::from("-"), |(elo, error)| format!("{elo:.1} +/- {error:.1}"));
+
+    format!(
+        "Games: {}  W: {}  D: {}  L: {}  Elo: {elo}  LLR: {:.2} [{:.2}, {:.2}]",
+        results.games(),
+        results.wins,
+        results.draws,
+        results.losses,
+        results.llr(config),
+        config.lower_bound(),
+        config.upper_bound()
+    )
+}
+
+/// The pairing of the ``game_i``th game, where each opening is played twice with the colours reversed
+const fn sprt_pairing(game_i: usize, opening_amt: usize) -> Pairing {
+    let pair_i = game_i / 2;
+    let (white, black) = if game_i.is_multiple_of(2) {
+        (DEV_ENGINE, BASE_ENGINE)
+    } else {
+        (BASE_ENGINE, DEV_ENGINE)
+    };
+
+    Pairing {
+        white,
+        black,
+        opening: pair_i % opening_amt,
+        round: pair_i,
+    }
+}
+
+fn parse_value<T: std::str::FromStr>(flag: &str, value: Option<&String>) -> Result<T, SprtError> {
+    value
+        .and_then(|value| value.parse().ok())
+        .ok_or_else(|| SprtError::UsageError(format!("Missing or invalid {flag} value")))
+}
+
+/// Play games between a dev engine and a base engine until the SPRT accepts a hypothesis (Or ``--max-games`` is reached)
+/// Games are played concurrently by ``--concurrency`` pairs of engine processes
+/// # Errors
+/// Returns an error if the arguments are invalid
+/// Returns an error if any engine can't be started or communicated with
+/// Returns an error if the PGN file can't be written
+#[allow(clippy::too_many_lines)]
+pub fn run_sprt_cli(args: &[String]) -> Result<(), SprtError> {
+    let mut sprt_config = SprtConfig::default();
+    let mut concurrency = 1_usize;
+    let mut max_games = None;
+
+    // Take out the SPRT arguments, the rest configure the games in the same way as a tournament
+    let mut tournament_args = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--elo0" => sprt_config.elo0 = parse_value(arg, args.next())?,
+            "--elo1" => sprt_config.elo1 = parse_value(arg, args.next())?,
+            "--alpha" => sprt_config.alpha = parse_value(arg, args.next())?,
+            "--beta" => sprt_config.beta = parse_value(arg, args.next())?,
+            "--concurrency" => concurrency = parse_value::<usize>(arg, args.next())?.max(1),
+            "--max-games" => max_games = Some(parse_value::<usize>(arg, args.next())?),
+            _ => tournament_args.push(arg.clone()),
+        }
+    }
+
+    let config = TournamentConfig::from_args(&tournament_args)?;
+    if config.engines.len() != 2 {
+        return Err(SprtError::UsageError(String::from(
+            "Exactly two engines (Dev then base) are needed",
+        )));
+    }
+    let is_probability = |value: f64| value > 0. && value < 1.;
+    if sprt_config.elo1 <= sprt_config.elo0 || !is_probability(sprt_config.alpha) || !is_probability(sprt_config.beta) {
+        return Err(SprtError::UsageError(String::from(
+            "elo1 must be greater than elo0, and alpha and beta must be between 0 and 1",
+        )));
+    }
+
+    fs::create_dir_all(&config.output_dir)?;
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs());
+    let mut pgn_file = File::create(config.output_dir.join(format!("sprt_{timestamp}.pgn")))?;
+
+    println!(
+        "SPRT {} vs {}: elo0 {} elo1 {} alpha {} beta {}, {concurrency} concurrent games",
+        config.engines[DEV_ENGINE].name,
+        config.engines[BASE_ENGINE].name,
+        sprt_config.elo0,
+        sprt_config.elo1,
+        sprt_config.alpha,
+        sprt_config.beta
+    );
+
+    let config = Arc::new(config);
+    let next_game = Arc::new(AtomicUsize::new(0));
+    let stop = Arc::new(AtomicBool::new(false));
+    let (result_tx, result_rx) = crossbeam_channel::unbounded();
+
+    // Each worker has its own pair of engines, and plays games until the test stops
+    let workers = (0..concurrency)
+        .map(|_| {
+            let config = Arc::clone(&config);
+            let next_game = Arc::clone(&next_game);
+            let stop = Arc::clone(&stop);
+            let result_tx = result_tx.clone();
+
+            std::thread::spawn(move || {
+                let result = play_sprt_games(&config, &next_game, &stop, max_games, &result_tx);
+
+                if let Err(e) = result {
+                    let _ = result_tx.send(Err(e));
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+
+    // Only the workers should keep the channel open
+    drop(result_tx);
+
+    let mut results = SprtResults::default();
+    let mut verdict = None;
+    let mut error = None;
+
+    for result in result_rx {
+        let (game_i, record) = match result {
+            Ok(game) => game,
+            Err(e) => {
+                stop.store(true, Ordering::SeqCst);
+                error.get_or_insert(e);
+                continue;
+            }
+        };
+
+        // Games which finish after the test has stopped are still saved, but don't change the result
+        pgn_file.write_all(
+            tournament_game_pgn(&config, &record, game_i + 1)
+                .map_err(TournamentError::from)?
+                .as_bytes(),
+        )?;
+        if verdict.is_some() {
+            continue;
+        }
+
+        results.add(&record);
+        println!("{}", describe_results(&results, &sprt_config));
+
+        verdict = sprt_verdict(results.llr(&sprt_config), &sprt_config);
+        if verdict.is_some() {
+            stop.store(true, Ordering::SeqCst);
+        }
+    }
+
+    for worker in workers {
+        let _ = worker.join();
+    }
+
+    if let Some(e) = error {
+        return Err(e.into());
+    }
+
+    println!(
+        "{}\n{}",
+        describe_results(&results, &sprt_config),
+        match verdict {
+            Some(SprtVerdict::AcceptH1) => "H1 accepted: The dev engine is stronger",
+            Some(SprtVerdict::AcceptH0) => "H0 accepted: The dev engine is not stronger",
+            None => "No hypothesis accepted before the maximum amount of games",
+        }
+    );
+
+    Ok(())
+}
+
+/// Start a dev and base engine, then play the next unplayed game until the test is stopped
+fn play_sprt_games(
+    config: &TournamentConfig,
+    next_game: &AtomicUsize,
+    stop: &AtomicBool,
+    max_games: Option<usize>,
+    result_tx: &crossbeam_channel::Sender<Result<(usize, GameRecord), TournamentError>>,
+) -> Result<(), TournamentError> {
+    let mut engines = config
+        .engines
+        .iter()
+        .map(|engine| UciEngine::start(&engine.command))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    while !stop.load(Ordering::SeqCst) {
+        let game_i = next_game.fetch_add(1, Ordering::SeqCst);
+        if max_games.is_some_and(|max_games| game_i >= max_games) {
+            break;
+        }
+
+        let pairing = sprt_pairing(game_i, config.openings.len());
+        let Ok([white, black]) = engines.get_disjoint_mut([pairing.white, pairing.black]) else {
+            break;
+        };
+
+        let (moves, result, termination) = play_game(
+            white,
+            black,
+            &config.openings[pairing.opening],
+            config.time_control,
+            config.adjudication,
+        )?;
+
+        let record = GameRecord {
+            pairing,
+            moves,
+            result,
+            termination,
+        };
+
+        if result_tx.send(Ok((game_i, record))).is_err() {
+            break;
+        }
+    }
+
+    for engine in &mut engines {
+        engine.quit()?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-6, "{actual} is not close to {expected}");
+    }
+
+    const fn results(wins: u32, draws: u32, losses: u32) -> SprtResults {
+        SprtResults { wins, draws, losses }
+    }
+
+    #[test]
+    fn bounds() {
+        let config = SprtConfig::default();
+
+        assert_close(config.lower_bound(), -2.944_438_979);
+        assert_close(config.upper_bound(), 2.944_438_979);
+    }
+
+    #[test]
+    fn elo_and_score_conversions() {
+        assert_close(elo_to_score(0.), 0.5);
+        assert_close(elo_to_score(100.), 0.640_065_000);
+        assert_close(elo_to_score(-100.), 0.359_935_000);
+
+        assert_close(score_to_elo(0.5), 0.);
+        assert_close(score_to_elo(0.75), 190.848_501_888);
+        assert_close(score_to_elo(elo_to_score(42.)), 42.);
+
+        // Perfect scores are clamped, so they stay finite
+        assert!(score_to_elo(1.).is_finite());
+        assert!(score_to_elo(0.).is_finite());
+    }
+
+    #[test]
+    fn llr_of_known_results() {
+        let config = SprtConfig::default();
+
+        assert_close(results(40, 50, 30).llr(&config), 0.228_104_720);
+        assert_close(results(30, 50, 40).llr(&config), -0.271_216_613);
+        assert_close(results(1000, 1000, 800).llr(&config), 4.058_180_250);
+    }
+
+    #[test]
+    fn elo_of_known_results() {
+        let (elo, error) = results(40, 50, 30).elo().expect("Results have games");
+        assert_close(elo, 29.020_266_859);
+        assert_close(error, 47.831_408_493);
+
+        let (elo, error) = results(30, 50, 40).elo().expect("Results have games");
+        assert_close(elo, -29.020_266_859);
+        assert_close(error, 47.831_408_493);
+    }
+
+    #[test]
+    fn zero_variance() {
+        let config = SprtConfig::default();
+
+        // No games, or every game having the same score, gives no information
+        assert_close(results(0, 0, 0).llr(&config), 0.);
+        assert!(results(0, 0, 0).elo().is_none());
+
+        assert_close(results(0, 20, 0).llr(&config), 0.);
+        assert_close(results(20, 0, 0).llr(&config), 0.);
+
+        let (elo, error) = results(0, 20, 0).elo().expect("Results have games");
+        assert_close(elo, 0.);
+        assert_close(error, 0.);
+    }
+
+    #[test]
+    fn verdicts() {
+        let config = SprtConfig::default();
+
+        assert_eq!(sprt_verdict(0., &config), None);
+        assert_eq!(sprt_verdict(2.95, &config), Some(SprtVerdict::AcceptH1));
+        assert_eq!(sprt_verdict(-2.95, &config), Some(SprtVerdict::AcceptH0));
+
+        assert_eq!(sprt_verdict(results(40, 50, 30).llr(&config), &config), None);
+        assert_eq!(
+            sprt_verdict(results(1000, 1000, 800).llr(&config), &config),
+            Some(SprtVerdict::AcceptH1)
+        );
+        assert_eq!(
+            sprt_verdict(results(600, 1000, 800).llr(&config), &config),
+            Some(SprtVerdict::AcceptH0)
+        );
+    }
+}
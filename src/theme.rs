@@ -0,0 +1,242 @@
+use std::{fs, path::Path};
+
+use bevy::prelude::*;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    bitboard_event::BitBoardMarker,
+    display::{get_piece_texture_atlas, square_colour, BoardOrientation, BoardSquare, CoordinateLabel, PIECE_SIZE},
+    last_move::LastMoveMarker,
+    piece::PieceSprite,
+    possible_moves::PossibleMoveMarker,
+    selection::SelectionMarker,
+};
+
+/// Folder of ``.ron`` theme files
+pub const THEME_DIR: &str = "assets/themes";
+
+#[derive(Error, Debug)]
+pub enum ThemeError {
+    #[error("Theme file could not be read:\n\t{0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("Theme file could not be parsed:\n\t{0}")]
+    RonError(#[from] ron::error::SpannedError),
+}
+
+/// A colour written as a hex string in a theme file (``"#rrggbb"`` or ``"#rrggbbaa"``)
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(try_from = "String")]
+pub struct ThemeColour(pub Color);
+
+impl TryFrom<String> for ThemeColour {
+    type Error = String;
+
+    fn try_from(hex: String) -> Result<Self, Self::Error> {
+        let digits = hex.trim_start_matches('#');
+        let channel = |i: usize| {
+            digits
+                .get(i * 2..i * 2 + 2)
+                .and_then(|channel| u8::from_str_radix(channel, 16).ok())
+                .ok_or_else(|| format!("Invalid colour \"{hex}\""))
+        };
+
+        match digits.len() {
+            6 => Ok(Self(Color::srgb_u8(channel(0)?, channel(1)?, channel(2)?))),
+            8 => Ok(Self(Color::srgba_u8(channel(0)?, channel(1)?, channel(2)?, channel(3)?))),
+            _ => Err(format!("Invalid colour \"{hex}\"")),
+        }
+    }
+}
+
+/// The colours of the board and its markers, and the sprite sheet used for the pieces
+#[derive(Debug, Clone, Deserialize)]
+pub struct Theme {
+    pub name: String,
+    pub light_square: ThemeColour,
+    pub dark_square: ThemeColour,
+    pub last_move: ThemeColour,
+    /// Colour of the piece selected by clicking (Themes from before selection was added use the default theme's colour)
+    #[serde(default = "default_selected_colour")]
+    pub selected: ThemeColour,
+    /// Colour of the tiles of queued premoves (Themes from before premoves were added use the default theme's colour)
+    #[serde(default = "default_premove_colour")]
+    pub premove: ThemeColour,
+    pub possible_move: ThemeColour,
+    /// Colour of the bitboard overlays
+    pub overlay: ThemeColour,
+    /// Sprite sheet of the pieces, relative to the assets folder (One row per colour, one column per piece)
+    pub piece_texture: String,
+    /// Size of each piece in the sprite sheet in pixels
+    pub piece_cell_size: u32,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            name: String::from("Default"),
+            light_square: ThemeColour(Color::WHITE),
+            dark_square: ThemeColour(Color::linear_rgb(0.4, 0.3, 0.8)),
+            last_move: ThemeColour(Color::linear_rgba(0.8, 0.8, 0.1, 1.0)),
+            selected: ThemeColour(Color::linear_rgba(0.1, 0.6, 0.9, 0.75)),
+            premove: ThemeColour(Color::linear_rgba(0.8, 0.1, 0.1, 0.75)),
+            possible_move: ThemeColour(Color::linear_rgba(0., 1., 0., 0.75)),
+            overlay: ThemeColour(Color::linear_rgba(1., 0., 0., 0.75)),
+            piece_texture: String::from("ChessPiecesArray.png"),
+            piece_cell_size: 150,
+        }
+    }
+}
+
+fn default_selected_colour() -> ThemeColour {
+    Theme::default().selected
+}
+
+fn default_premove_colour() -> ThemeColour {
+    Theme::default().premove
+}
+
+impl Theme {
+    /// Load a theme from a ``.ron`` file
+    /// # Errors
+    /// Returns an error if the file can't be read or parsed
+    pub fn load(path: &Path) -> Result<Self, ThemeError> {
+        Ok(ron::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    /// How much the piece sprites are scaled by to fill a square
+    #[must_use]
+    pub fn piece_scale(&self) -> f32 {
+        PIECE_SIZE / self.piece_cell_size as f32
+    }
+}
+
+/// Every theme in ``THEME_DIR``, and the one which is currently being used
+#[derive(Resource, Debug, Clone)]
+pub struct ThemeState {
+    pub themes: Vec<Theme>,
+    pub current: usize,
+}
+
+impl Default for ThemeState {
+    fn default() -> Self {
+        let mut themes = fs::read_dir(THEME_DIR)
+            .map(|entries| {
+                entries
+                    .filter_map(Result::ok)
+                    .map(|entry| entry.path())
+                    .filter(|path| path.extension().is_some_and(|extension| extension == "ron"))
+                    .filter_map(|path| {
+                        Theme::load(&path)
+                            .inspect_err(|e| eprintln!("Theme {} could not be loaded: {e}", path.display()))
+                            .ok()
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+
+        // Start with the default theme, then the rest in alphabetical order
+        themes.sort_by(|a, b| {
+            (a.name != Theme::default().name)
+                .cmp(&(b.name != Theme::default().name))
+                .then(a.name.cmp(&b.name))
+        });
+
+        if themes.is_empty() {
+            themes.push(Theme::default());
+        }
+
+        Self { themes, current: 0 }
+    }
+}
+
+impl ThemeState {
+    #[must_use]
+    pub fn theme(&self) -> &Theme {
+        &self.themes[self.current]
+    }
+}
+
+#[derive(Event)]
+pub struct ThemeCycleEvent {
+    pub backwards: bool,
+}
+
+/// Switch to the next (Or previous) theme, recolouring the board and swapping the piece textures in place
+#[allow(clippy::needless_pass_by_value)]
+#[allow(clippy::type_complexity)]
+#[allow(clippy::too_many_arguments)]
+pub fn theme_cycle_event_handler(
+    mut ev_theme_cycle: EventReader<ThemeCycleEvent>,
+    mut theme_state: ResMut<ThemeState>,
+    asset_server: Res<AssetServer>,
+    mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+    mut square_query: Query<(&mut Sprite, &BoardSquare), Without<PieceSprite>>,
+    mut label_query: Query<(&mut TextColor, &CoordinateLabel)>,
+    mut marker_query: Query<
+        (&mut Sprite, Has<LastMoveMarker>, Has<SelectionMarker>),
+        (
+            Or<(With<LastMoveMarker>, With<BitBoardMarker>, With<SelectionMarker>)>,
+            Without<BoardSquare>,
+            Without<PieceSprite>,
+        ),
+    >,
+    mut piece_query: Query<(&mut Sprite, &mut Transform), With<PieceSprite>>,
+    possible_move_query: Query<&MeshMaterial2d<ColorMaterial>, With<PossibleMoveMarker>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    orientation: Res<BoardOrientation>,
+) {
+    for ev in ev_theme_cycle.read() {
+        let theme_amt = theme_state.themes.len();
+        theme_state.current = if ev.backwards {
+            (theme_state.current + theme_amt - 1) % theme_amt
+        } else {
+            (theme_state.current + 1) % theme_amt
+        };
+
+        let theme = theme_state.theme();
+
+        for (mut sprite, square) in &mut square_query {
+            sprite.color = square_colour(theme, square.file, square.rank);
+        }
+
+        for (mut colour, label) in &mut label_query {
+            let (file, rank) = if label.is_file {
+                orientation.orient(label.slot, 0)
+            } else {
+                orientation.orient(0, label.slot)
+            };
+
+            colour.0 = square_colour(theme, file + 1, rank);
+        }
+
+        for (mut sprite, is_last_move, is_selection) in &mut marker_query {
+            sprite.color = if is_last_move {
+                theme.last_move.0
+            } else if is_selection {
+                theme.selected.0
+            } else {
+                theme.overlay.0
+            };
+        }
+
+        // Possible moves are drawn as dots and rings, which are coloured by their material
+        for material in &possible_move_query {
+            if let Some(material) = materials.get_mut(&material.0) {
+                material.color = theme.possible_move.0;
+            }
+        }
+
+        let (texture, texture_atlas_layout) = get_piece_texture_atlas(&asset_server, &mut texture_atlas_layouts, theme);
+
+        for (mut sprite, mut transform) in &mut piece_query {
+            sprite.image = texture.clone();
+            if let Some(texture_atlas) = sprite.texture_atlas.as_mut() {
+                texture_atlas.layout = texture_atlas_layout.clone();
+            }
+
+            transform.scale = Vec3::splat(theme.piece_scale());
+        }
+    }
+}
@@ -0,0 +1,120 @@
+use bevy::prelude::*;
+
+use chess_core::piece_move::PieceMove;
+
+use crate::{
+    arrow::spawn_arrow,
+    board::{is_in_check, BoardBevy},
+    display::BoardOrientation,
+    eval_cache::board_after_moves,
+    uci::{transmit_to_uci, UciError, UciMessage},
+};
+
+/// Depth used when searching for the opponent's threat
+pub const THREAT_DEPTH: u32 = 12;
+
+const THREAT_COLOUR: Color = Color::linear_rgba(0.9, 0.1, 0.1, 0.8);
+
+/// The move the opponent would play if it were their turn in the position after ``move_history``
+#[derive(Debug, Clone)]
+pub struct Threat {
+    pub move_history: String,
+    /// ``None`` if the player to move is in check, or the opponent has no moves
+    pub piece_move: Option<PieceMove>,
+}
+
+#[derive(Resource, Debug, Clone, Default)]
+pub struct ThreatState {
+    pub enabled: bool,
+    pub threat: Option<Threat>,
+    /// The move history of the position which the engine is currently finding the threat for
+    pub pending: Option<String>,
+}
+
+#[derive(Event)]
+pub struct ThreatToggleEvent;
+
+#[derive(Component)]
+pub struct ThreatMarker;
+
+/// The FEN of the position after ``move_history``, but with the other player to move (A null move)
+/// Returns ``None`` if the player to move is in check, since passing would let the opponent take the king
+/// # Errors
+/// Returns an error if the moves in ``move_history`` can't be parsed
+pub fn null_move_fen(move_history: &str) -> Result<Option<String>, UciError> {
+    let board = board_after_moves(move_history)?;
+
+    if is_in_check(&board, board.get_player()) {
+        return Ok(None);
+    }
+
+    let mut fields = board.to_fen().split_whitespace().map(ToString::to_string).collect::<Vec<_>>();
+
+    if fields.len() < 4 {
+        return Ok(None);
+    }
+
+    // Flip the player to move, and remove the en passant tile since it can only be taken straight after the pawn moved
+    fields[1] = String::from(if fields[1] == "w" { "b" } else { "w" });
+    fields[3] = String::from("-");
+
+    Ok(Some(fields.join(" ")))
+}
+
+pub fn threat_toggle_event_handler(mut ev_threat_toggle: EventReader<ThreatToggleEvent>, mut threat_state: ResMut<ThreatState>) {
+    for _ in ev_threat_toggle.read() {
+        threat_state.enabled = !threat_state.enabled;
+    }
+}
+
+/// Ask the engine for the threat in each new position while threats are enabled, and draw it as an arrow
+/// # Panics
+/// Panics if message cannot be sent via uci
+#[allow(clippy::needless_pass_by_value)]
+pub fn update_threat_display(
+    mut commands: Commands,
+    mut threat_state: ResMut<ThreatState>,
+    board: Res<BoardBevy>,
+    threat_entities: Query<Entity, With<ThreatMarker>>,
+    orientation: Res<BoardOrientation>,
+) {
+    // Redraw when the board is flipped too
+    if !threat_state.is_changed() && !board.is_changed() && !orientation.is_changed() {
+        return;
+    }
+
+    for entity in threat_entities.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    if !threat_state.enabled {
+        return;
+    }
+
+    let Ok(move_history) = board.board.move_history.to_piece_move_string() else {
+        return;
+    };
+
+    match &threat_state.threat {
+        Some(threat) if threat.move_history == move_history => {
+            if let Some(piece_move) = threat.piece_move {
+                spawn_arrow(
+                    &mut commands,
+                    piece_move.from,
+                    piece_move.to,
+                    THREAT_COLOUR,
+                    ThreatMarker,
+                    *orientation,
+                );
+            }
+        }
+        _ => {
+            // Only ask for each position once
+            if threat_state.pending.as_ref() != Some(&move_history) {
+                threat_state.pending = Some(move_history.clone());
+
+                transmit_to_uci(UciMessage::Threat { move_history }).unwrap_or_else(|e| panic!("{e}"));
+            }
+        }
+    }
+}
@@ -0,0 +1,849 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    fs::{self, File},
+    io::Write,
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use bevy::prelude::*;
+use thiserror::Error;
+
+use chess_core::board::{Board, Player, DEFAULT_FEN};
+
+use crate::{
+    display::{SidePanel, TOP_UI_HEIGHT},
+    eval_cache::fen_key,
+    eval_graph::EvalHistory,
+    pgn::{annotated_pgn, find_legal_move, parse_pgn, PgnError},
+    uci::{UciEngine, UciError},
+    uci_info::UciEval,
+};
+
+pub const TOURNAMENT_DIR: &str = "tournaments";
+/// Arguments used when a tournament is started from the GUI, in the same format as the ``tournament`` subcommand
+pub const TOURNAMENT_CONFIG_FILE: &str = "tournament.cfg";
+
+/// Mates are counted as this many centipawns when adjudicating
+const MATE_CENTIPAWNS: i32 = 100_000;
+/// A position which occurs this many times is a draw
+const REPETITION_DRAW_AMT: usize = 3;
+/// A game is drawn once this many plies have passed without a capture or pawn move
+const FIFTY_MOVE_RULE_PLIES: usize = 100;
+
+const PANEL_WIDTH: Val = Val::Px(600.0);
+const PANEL_TOP: Val = Val::Px(TOP_UI_HEIGHT);
+const PANEL_COLOUR: Color = Color::linear_rgba(0.1, 0.1, 0.1, 0.85);
+const FONT_SIZE: f32 = 18.;
+
+#[derive(Error, Debug)]
+pub enum TournamentError {
+    #[error(
+        "Usage: chess_gui tournament --engine <name>=<command> --engine <name>=<command> [--engine ...] [--gauntlet] [--rounds <amount>] [--openings <epd/pgn file>] [--tc <seconds>+<increment>] [--resign <centipawns> <moves>] [--draw <centipawns> <moves> <from ply>] [--max-plies <plies>] [--out <directory>]\n\t{0}"
+    )]
+    UsageError(String),
+
+    #[error("Tournament files could not be read/wrote to:\n\t{0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error(transparent)]
+    UciError(#[from] UciError),
+
+    #[error(transparent)]
+    PgnError(#[from] PgnError),
+
+    #[error("Opening could not be parsed:\n\t{0}")]
+    OpeningParseError(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct EngineConfig {
+    pub name: String,
+    pub command: String,
+}
+
+impl EngineConfig {
+    /// Parse an engine from ``name=command``, or from just the command (Using the name of its executable)
+    #[must_use]
+    pub fn parse(engine: &str) -> Self {
+        match engine.split_once('=') {
+            Some((name, command)) => Self {
+                name: name.to_string(),
+                command: command.to_string(),
+            },
+            None => Self {
+                // Leave out any arguments given to the executable
+                name: Path::new(engine.split_whitespace().next().unwrap_or(engine))
+                    .file_name()
+                    .map_or_else(|| engine.to_string(), |name| name.to_string_lossy().to_string()),
+                command: engine.to_string(),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct TimeControl {
+    pub base: Duration,
+    pub increment: Duration,
+}
+
+impl Default for TimeControl {
+    fn default() -> Self {
+        Self {
+            base: Duration::from_mins(1),
+            increment: Duration::from_secs(1),
+        }
+    }
+}
+
+impl TimeControl {
+    /// Parse a time control in seconds, with an optional increment (e.g. ``60+0.5``)
+    /// # Errors
+    /// Returns an error if either time can't be parsed
+    pub fn parse(time_control: &str) -> Result<Self, TournamentError> {
+        let parse_seconds = |seconds: &str| {
+            seconds
+                .parse::<f64>()
+                .ok()
+                .filter(|seconds| *seconds >= 0.)
+                .map(Duration::from_secs_f64)
+                .ok_or_else(|| TournamentError::UsageError(format!("Invalid time control \"{time_control}\"")))
+        };
+
+        let (base, increment) = time_control.split_once('+').unwrap_or((time_control, "0"));
+
+        Ok(Self {
+            base: parse_seconds(base)?,
+            increment: parse_seconds(increment)?,
+        })
+    }
+
+    /// The UCI ``go`` command for a search with these clocks, indexed by ``Player::to_index``
+    #[must_use]
+    pub fn go_command(&self, clocks: [Duration; 2]) -> String {
+        format!(
+            "go wtime {} btime {} winc {} binc {}",
+            clocks[Player::White.to_index()].as_millis(),
+            clocks[Player::Black.to_index()].as_millis(),
+            self.increment.as_millis(),
+            self.increment.as_millis()
+        )
+    }
+}
+
+impl fmt::Display for TimeControl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}+{}", self.base.as_secs_f64(), self.increment.as_secs_f64())
+    }
+}
+
+/// When to end a game early, using the evals reported by the engines
+#[derive(Debug, Clone, Copy)]
+pub struct Adjudication {
+    /// A player loses once their own eval has been at or below ``-resign_centipawns`` for ``resign_moves`` of their moves in a row
+    pub resign_centipawns: Option<i32>,
+    pub resign_moves: usize,
+    /// The game is drawn once both evals have been within ``draw_centipawns`` of zero for ``draw_moves`` moves each, after ``draw_min_ply``
+    pub draw_centipawns: Option<i32>,
+    pub draw_moves: usize,
+    pub draw_min_ply: usize,
+    /// The game is drawn once it reaches this many plies
+    pub max_plies: usize,
+}
+
+impl Default for Adjudication {
+    fn default() -> Self {
+        Self {
+            resign_centipawns: Some(1000),
+            resign_moves: 3,
+            draw_centipawns: Some(10),
+            draw_moves: 8,
+            draw_min_ply: 80,
+            max_plies: 400,
+        }
+    }
+}
+
+/// A position to start games from, given by a FEN and any moves played after it
+#[derive(Debug, Clone)]
+pub struct Opening {
+    pub fen: String,
+    pub moves: Vec<String>,
+}
+
+impl Default for Opening {
+    fn default() -> Self {
+        Self {
+            fen: DEFAULT_FEN.to_string(),
+            moves: Vec::new(),
+        }
+    }
+}
+
+/// Load the openings from an EPD file (One position per line), or from the games in a PGN file
+/// # Errors
+/// Returns an error if the file can't be read
+/// Returns an error if any position or game can't be parsed
+pub fn load_openings(path: &Path) -> Result<Vec<Opening>, TournamentError> {
+    let text = fs::read_to_string(path)?;
+
+    if path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("epd"))
+    {
+        text.lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                // EPD positions have no move clocks, only the first four fields of a FEN, followed by operations
+                let fields = line.split_whitespace().take(4).collect::<Vec<_>>();
+                if fields.len() < 4 {
+                    return Err(TournamentError::OpeningParseError(line.to_string()));
+                }
+
+                let fen = format!("{} 0 1", fields.join(" "));
+                Board::from_fen(&fen).map_err(TournamentError::OpeningParseError)?;
+
+                Ok(Opening { fen, moves: Vec::new() })
+            })
+            .collect()
+    } else {
+        Ok(parse_pgn(&text)
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .map(|game| Opening {
+                fen: game.get_header("FEN").unwrap_or(DEFAULT_FEN).to_string(),
+                moves: game.moves,
+            })
+            .collect())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TournamentFormat {
+    /// Every engine plays every other engine
+    RoundRobin,
+    /// The first engine plays every other engine
+    Gauntlet,
+}
+
+/// A game to be played in a tournament, with the engines given by their index
+#[derive(Debug, Clone, Copy)]
+pub struct Pairing {
+    pub white: usize,
+    pub black: usize,
+    pub opening: usize,
+    pub round: usize,
+}
+
+/// Create the games of a tournament, where each pair of engines plays each opening twice with the colours reversed
+#[must_use]
+pub fn pairings(engine_amt: usize, format: TournamentFormat, rounds: usize, opening_amt: usize) -> Vec<Pairing> {
+    let engine_pairs = (0..engine_amt)
+        .flat_map(|a| (a + 1..engine_amt).map(move |b| (a, b)))
+        .filter(|(a, _)| format == TournamentFormat::RoundRobin || *a == 0)
+        .collect::<Vec<_>>();
+
+    let pair_amt = engine_pairs.len();
+
+    (0..rounds)
+        .flat_map(|round| {
+            engine_pairs.iter().enumerate().flat_map(move |(pair_i, &(a, b))| {
+                // Cycle through the openings by pair and then by round, so a single round still uses more than one opening
+                let opening = (round * pair_amt + pair_i) % opening_amt.max(1);
+
+                [(a, b), (b, a)].map(|(white, black)| Pairing {
+                    white,
+                    black,
+                    opening,
+                    round,
+                })
+            })
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameResult {
+    Win(Player),
+    Draw,
+}
+
+impl GameResult {
+    #[must_use]
+    pub const fn to_pgn(self) -> &'static str {
+        match self {
+            Self::Win(Player::White) => "1-0",
+            Self::Win(Player::Black) => "0-1",
+            Self::Draw => "1/2-1/2",
+        }
+    }
+
+    /// The amount of half points scored by ``player``
+    #[must_use]
+    pub fn half_points(self, player: Player) -> u32 {
+        match self {
+            Self::Win(winner) if winner == player => 2,
+            Self::Win(_) => 0,
+            Self::Draw => 1,
+        }
+    }
+}
+
+/// A finished tournament game, with all of its moves (Including the opening) in long algebraic notation
+#[derive(Debug, Clone)]
+pub struct GameRecord {
+    pub pairing: Pairing,
+    pub moves: Vec<String>,
+    pub result: GameResult,
+    pub termination: String,
+}
+
+/// The scores of every engine against every other engine
+#[derive(Debug, Clone, Default)]
+pub struct Crosstable {
+    pub names: Vec<String>,
+    /// Half points scored by the row engine against the column engine
+    pub half_points: Vec<Vec<u32>>,
+    /// Games played by the row engine against the column engine
+    pub games: Vec<Vec<u32>>,
+}
+
+impl Crosstable {
+    #[must_use]
+    pub fn new(names: Vec<String>) -> Self {
+        let engine_amt = names.len();
+
+        Self {
+            names,
+            half_points: vec![vec![0; engine_amt]; engine_amt],
+            games: vec![vec![0; engine_amt]; engine_amt],
+        }
+    }
+
+    pub fn add(&mut self, record: &GameRecord) {
+        let Pairing { white, black, .. } = record.pairing;
+
+        self.half_points[white][black] += record.result.half_points(Player::White);
+        self.half_points[black][white] += record.result.half_points(Player::Black);
+        self.games[white][black] += 1;
+        self.games[black][white] += 1;
+    }
+
+    #[must_use]
+    pub fn points(&self, engine: usize) -> f32 {
+        self.half_points[engine].iter().sum::<u32>() as f32 / 2.
+    }
+
+    #[must_use]
+    pub fn games_played(&self, engine: usize) -> u32 {
+        self.games[engine].iter().sum()
+    }
+}
+
+impl fmt::Display for Crosstable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name_width = self.names.iter().map(String::len).max().unwrap_or_default().max(6);
+
+        // Rank the engines by their points
+        let mut ranking = (0..self.names.len()).collect::<Vec<_>>();
+        ranking.sort_by(|&a, &b| self.points(b).total_cmp(&self.points(a)));
+
+        write!(f, "Rank  {:name_width$}  Points  Games |", "Engine")?;
+        for i in 0..self.names.len() {
+            write!(f, " {:>5}", i + 1)?;
+        }
+        writeln!(f)?;
+
+        for (rank, &engine) in ranking.iter().enumerate() {
+            write!(
+                f,
+                "{:<4}  {:name_width$}  {:>6.1}  {:>5} |",
+                rank + 1,
+                self.names[engine],
+                self.points(engine),
+                self.games_played(engine)
+            )?;
+
+            for opponent in 0..self.names.len() {
+                if opponent == engine || self.games[engine][opponent] == 0 {
+                    write!(f, " {:>5}", "-")?;
+                } else {
+                    write!(f, " {:>5.1}", self.half_points[engine][opponent] as f32 / 2.)?;
+                }
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TournamentConfig {
+    pub engines: Vec<EngineConfig>,
+    pub format: TournamentFormat,
+    pub rounds: usize,
+    pub openings: Vec<Opening>,
+    pub time_control: TimeControl,
+    pub adjudication: Adjudication,
+    pub output_dir: PathBuf,
+}
+
+impl TournamentConfig {
+    /// # Errors
+    /// Returns an error if the arguments don't match the usage of the ``tournament`` subcommand
+    /// Returns an error if the openings can't be loaded
+    pub fn from_args<S: AsRef<str>>(args: &[S]) -> Result<Self, TournamentError> {
+        let mut config = Self {
+            engines: Vec::new(),
+            format: TournamentFormat::RoundRobin,
+            rounds: 1,
+            openings: vec![Opening::default()],
+            time_control: TimeControl::default(),
+            adjudication: Adjudication::default(),
+            output_dir: PathBuf::from(TOURNAMENT_DIR),
+        };
+
+        let mut args = args.iter().map(AsRef::as_ref);
+        let mut next_value = |flag: &str| {
+            args.next()
+                .ok_or_else(|| TournamentError::UsageError(format!("Missing {flag} value")))
+        };
+
+        while let Ok(arg) = next_value("argument") {
+            match arg {
+                "--engine" => config.engines.push(EngineConfig::parse(next_value(arg)?)),
+                "--gauntlet" => config.format = TournamentFormat::Gauntlet,
+                "--rounds" => config.rounds = parse_value(arg, next_value(arg)?)?,
+                "--openings" => config.openings = load_openings(Path::new(next_value(arg)?))?,
+                "--tc" => config.time_control = TimeControl::parse(next_value(arg)?)?,
+                "--resign" => {
+                    config.adjudication.resign_centipawns = Some(parse_value(arg, next_value(arg)?)?);
+                    config.adjudication.resign_moves = parse_value(arg, next_value(arg)?)?;
+                }
+                "--no-resign" => config.adjudication.resign_centipawns = None,
+                "--draw" => {
+                    config.adjudication.draw_centipawns = Some(parse_value(arg, next_value(arg)?)?);
+                    config.adjudication.draw_moves = parse_value(arg, next_value(arg)?)?;
+                    config.adjudication.draw_min_ply = parse_value(arg, next_value(arg)?)?;
+                }
+                "--no-draw" => config.adjudication.draw_centipawns = None,
+                "--max-plies" => config.adjudication.max_plies = parse_value(arg, next_value(arg)?)?,
+                "--out" => config.output_dir = PathBuf::from(next_value(arg)?),
+                _ => return Err(TournamentError::UsageError(format!("Unknown argument \"{arg}\""))),
+            }
+        }
+
+        if config.engines.len() < 2 {
+            return Err(TournamentError::UsageError(String::from("At least two engines are needed")));
+        }
+        if config.openings.is_empty() {
+            return Err(TournamentError::UsageError(String::from("The openings file has no openings")));
+        }
+
+        Ok(config)
+    }
+}
+
+fn parse_value<T: std::str::FromStr>(flag: &str, value: &str) -> Result<T, TournamentError> {
+    value
+        .parse()
+        .map_err(|_| TournamentError::UsageError(format!("Invalid {flag} value \"{value}\"")))
+}
+
+/// The eval in centipawns from the perspective of the player it was reported by
+const fn adjudication_centipawns(eval: UciEval) -> i32 {
+    match eval {
+        UciEval::Centipawn(eval) => eval,
+        UciEval::Mate(mate_in) => {
+            if mate_in > 0 {
+                MATE_CENTIPAWNS
+            } else {
+                -MATE_CENTIPAWNS
+            }
+        }
+    }
+}
+
+/// Play a game between ``white`` and ``black`` from ``opening``, returning the moves, result, and reason the game ended
+/// # Errors
+/// Returns an error if the opening can't be parsed
+/// Returns an error if either engine can't be communicated with
+#[allow(clippy::too_many_lines)]
+pub fn play_game(
+    white: &mut UciEngine,
+    black: &mut UciEngine,
+    opening: &Opening,
+    time_control: TimeControl,
+    adjudication: Adjudication,
+) -> Result<(Vec<String>, GameResult, String), TournamentError> {
+    let mut board = Board::from_fen(&opening.fen).map_err(TournamentError::OpeningParseError)?;
+    let mut moves = Vec::new();
+
+    for algebraic in &opening.moves {
+        board.apply_move(find_legal_move(&board, algebraic)?);
+        moves.push(algebraic.clone());
+    }
+
+    white.new_game()?;
+    black.new_game()?;
+
+    let mut clocks = [time_control.base; 2];
+    let mut position_counts = HashMap::from([(fen_key(&board.to_fen()), 1_usize)]);
+    let mut resign_counts = [0; 2];
+    let mut draw_count = 0;
+
+    loop {
+        match board.has_game_ended() {
+            Some(Some(winner)) => return Ok((moves, GameResult::Win(winner), String::from("Checkmate"))),
+            Some(None) => return Ok((moves, GameResult::Draw, String::from("Stalemate"))),
+            None => {}
+        }
+
+        if moves.len() >= adjudication.max_plies {
+            return Ok((moves, GameResult::Draw, String::from("Adjudication: Maximum game length")));
+        }
+
+        let player = board.get_player();
+        let opponent = if player == Player::White {
+            Player::Black
+        } else {
+            Player::White
+        };
+        let engine = if player == Player::White { &mut *white } else { &mut *black };
+
+        let position = if moves.is_empty() {
+            format!("fen {}", opening.fen)
+        } else {
+            format!("fen {} moves {}", opening.fen, moves.join(" "))
+        };
+
+        let search_start = Instant::now();
+        let search = engine.go(&position, &time_control.go_command(clocks))?;
+        let elapsed = search_start.elapsed();
+
+        // Take the time used from the player's clock
+        let clock = &mut clocks[player.to_index()];
+        let Some(time_left) = clock.checked_sub(elapsed) else {
+            return Ok((moves, GameResult::Win(opponent), String::from("Time forfeit")));
+        };
+        *clock = time_left + time_control.increment;
+
+        let Ok(piece_move) = find_legal_move(&board, &search.best_move) else {
+            return Ok((
+                moves,
+                GameResult::Win(opponent),
+                format!("Illegal move \"{}\"", search.best_move),
+            ));
+        };
+
+        board.apply_move(piece_move);
+        moves.push(search.best_move);
+
+        let eval = adjudication_centipawns(search.info.eval);
+
+        if let Some(resign_centipawns) = adjudication.resign_centipawns {
+            let resign_count = &mut resign_counts[player.to_index()];
+            *resign_count = if eval <= -resign_centipawns { *resign_count + 1 } else { 0 };
+
+            if *resign_count >= adjudication.resign_moves {
+                return Ok((moves, GameResult::Win(opponent), String::from("Adjudication: Resignation")));
+            }
+        }
+
+        if let Some(draw_centipawns) = adjudication.draw_centipawns {
+            draw_count = if moves.len() >= adjudication.draw_min_ply && eval.abs() <= draw_centipawns {
+                draw_count + 1
+            } else {
+                0
+            };
+
+            if draw_count >= adjudication.draw_moves * 2 {
+                return Ok((moves, GameResult::Draw, String::from("Adjudication: Draw")));
+            }
+        }
+
+        let fen = board.to_fen();
+
+        let position_count = position_counts.entry(fen_key(&fen)).or_default();
+        *position_count += 1;
+        if *position_count >= REPETITION_DRAW_AMT {
+            return Ok((moves, GameResult::Draw, String::from("Threefold repetition")));
+        }
+
+        if fen
+            .split_whitespace()
+            .nth(4)
+            .and_then(|halfmove_clock| halfmove_clock.parse::<usize>().ok())
+            .is_some_and(|halfmove_clock| halfmove_clock >= FIFTY_MOVE_RULE_PLIES)
+        {
+            return Ok((moves, GameResult::Draw, String::from("Fifty move rule")));
+        }
+    }
+}
+
+/// The PGN of a finished tournament game
+/// # Errors
+/// Returns an error if the game's moves can't be converted into PGN
+pub fn tournament_game_pgn(config: &TournamentConfig, record: &GameRecord, game_number: usize) -> Result<String, PgnError> {
+    let opening = &config.openings[record.pairing.opening];
+
+    let mut headers = [
+        ("Event", String::from("Engine Tournament")),
+        ("Site", String::from("Chess GUI")),
+        ("Date", String::from("????.??.??")),
+        ("Round", format!("{}.{game_number}", record.pairing.round + 1)),
+        ("White", config.engines[record.pairing.white].name.clone()),
+        ("Black", config.engines[record.pairing.black].name.clone()),
+        ("Result", record.result.to_pgn().to_string()),
+        ("TimeControl", config.time_control.to_string()),
+        ("Termination", record.termination.clone()),
+    ]
+    .into_iter()
+    .map(|(key, value)| (key.to_string(), value))
+    .collect::<Vec<_>>();
+
+    if opening.fen != DEFAULT_FEN {
+        headers.push((String::from("SetUp"), String::from("1")));
+        headers.push((String::from("FEN"), opening.fen.clone()));
+    }
+
+    annotated_pgn(&record.moves, &EvalHistory::default(), &headers)
+}
+
+/// Play every game of the tournament, writing them all to a PGN file in the output directory
+/// ``on_game`` is called with each game as soon as it is finished, along with the crosstable so far
+/// # Errors
+/// Returns an error if any engine can't be started or communicated with
+/// Returns an error if the PGN file can't be written
+pub fn run_tournament(
+    config: &TournamentConfig,
+    mut on_game: impl FnMut(&GameRecord, &Crosstable),
+) -> Result<Crosstable, TournamentError> {
+    let mut engines = config
+        .engines
+        .iter()
+        .map(|engine| UciEngine::start(&engine.command))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    fs::create_dir_all(&config.output_dir)?;
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs());
+    let mut pgn_file = File::create(config.output_dir.join(format!("tournament_{timestamp}.pgn")))?;
+
+    let mut crosstable = Crosstable::new(config.engines.iter().map(|engine| engine.name.clone()).collect());
+
+    for (game_i, pairing) in pairings(config.engines.len(), config.format, config.rounds, config.openings.len())
+        .into_iter()
+        .enumerate()
+    {
+        let Ok([white, black]) = engines.get_disjoint_mut([pairing.white, pairing.black]) else {
+            continue;
+        };
+
+        let (moves, result, termination) = play_game(
+            white,
+            black,
+            &config.openings[pairing.opening],
+            config.time_control,
+            config.adjudication,
+        )?;
+
+        let record = GameRecord {
+            pairing,
+            moves,
+            result,
+            termination,
+        };
+
+        crosstable.add(&record);
+        pgn_file.write_all(tournament_game_pgn(config, &record, game_i + 1)?.as_bytes())?;
+
+        on_game(&record, &crosstable);
+    }
+
+    for engine in &mut engines {
+        engine.quit()?;
+    }
+
+    Ok(crosstable)
+}
+
+/// Describe a finished game in one line (e.g. ``Game 3: dev vs base 1-0 (Checkmate)``)
+#[must_use]
+pub fn describe_game(config: &TournamentConfig, record: &GameRecord, game_number: usize) -> String {
+    format!(
+        "Game {game_number}: {} vs {} {} ({})",
+        config.engines[record.pairing.white].name,
+        config.engines[record.pairing.black].name,
+        record.result.to_pgn(),
+        record.termination
+    )
+}
+
+/// Run a tournament from the command line, printing the crosstable after every game
+/// # Errors
+/// Returns an error if the arguments are invalid
+/// Returns an error if the tournament can't be played
+pub fn run_tournament_cli(args: &[String]) -> Result<(), TournamentError> {
+    let config = TournamentConfig::from_args(args)?;
+
+    let mut game_number = 0;
+    run_tournament(&config, |record, crosstable| {
+        game_number += 1;
+
+        println!("{}\n{crosstable}", describe_game(&config, record, game_number));
+    })?;
+
+    println!("Tournament complete, games saved to {}", config.output_dir.display());
+
+    Ok(())
+}
+
+/// Updates sent from a tournament running in the background to the GUI
+#[derive(Debug, Clone)]
+pub enum TournamentUpdate {
+    Game { description: String, crosstable: String },
+    Finished,
+    Error(String),
+}
+
+#[derive(Resource, Default)]
+pub struct TournamentState {
+    pub receiver: Option<crossbeam_channel::Receiver<TournamentUpdate>>,
+}
+
+#[derive(Event)]
+pub struct TournamentStartEvent;
+
+#[derive(Component)]
+pub struct TournamentPanel;
+
+#[derive(Component)]
+pub struct TournamentPanelText;
+
+pub fn create_tournament_panel(mut commands: Commands) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: PANEL_TOP,
+                left: Val::Px(0.),
+                width: PANEL_WIDTH,
+                flex_direction: FlexDirection::Column,
+                padding: UiRect::all(Val::Px(8.)),
+                ..default()
+            },
+            BackgroundColor(PANEL_COLOUR),
+            Visibility::Hidden,
+            TournamentPanel,
+            SidePanel,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(""),
+                TextFont {
+                    font_size: FONT_SIZE,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                TournamentPanelText,
+            ));
+        });
+}
+
+/// Start a tournament in the background using the arguments in ``TOURNAMENT_CONFIG_FILE``
+pub fn tournament_start_event_handler(
+    mut ev_tournament_start: EventReader<TournamentStartEvent>,
+    mut tournament_state: ResMut<TournamentState>,
+    mut panel_query: Query<&mut Visibility, With<TournamentPanel>>,
+    mut text_query: Query<&mut Text, With<TournamentPanelText>>,
+) {
+    for _ in ev_tournament_start.read() {
+        if tournament_state.receiver.is_some() {
+            continue;
+        }
+
+        for mut visibility in &mut panel_query {
+            *visibility = Visibility::Inherited;
+        }
+
+        // Arguments can be split over multiple lines, with comments starting with '#'
+        let config = fs::read_to_string(TOURNAMENT_CONFIG_FILE)
+            .map_err(TournamentError::from)
+            .and_then(|text| {
+                TournamentConfig::from_args(
+                    &text
+                        .lines()
+                        .flat_map(|line| line.split('#').next().unwrap_or_default().split_whitespace())
+                        .collect::<Vec<_>>(),
+                )
+            });
+
+        let config = match config {
+            Ok(config) => config,
+            Err(e) => {
+                for mut text in &mut text_query {
+                    *text = Text::new(format!("{TOURNAMENT_CONFIG_FILE}: {e}"));
+                }
+                continue;
+            }
+        };
+
+        for mut text in &mut text_query {
+            *text = Text::new(format!("Tournament started with {} engines", config.engines.len()));
+        }
+
+        let (tx, rx) = crossbeam_channel::unbounded();
+        tournament_state.receiver = Some(rx);
+
+        std::thread::spawn(move || {
+            let mut game_number = 0;
+            let result = run_tournament(&config, |record, crosstable| {
+                game_number += 1;
+
+                let _ = tx.send(TournamentUpdate::Game {
+                    description: describe_game(&config, record, game_number),
+                    crosstable: crosstable.to_string(),
+                });
+            });
+
+            let _ = tx.send(match result {
+                Ok(_) => TournamentUpdate::Finished,
+                Err(e) => TournamentUpdate::Error(e.to_string()),
+            });
+        });
+    }
+}
+
+/// Show the live crosstable of the running tournament
+pub fn update_tournament_panel(
+    mut tournament_state: ResMut<TournamentState>,
+    mut text_query: Query<&mut Text, With<TournamentPanelText>>,
+) {
+    let Some(receiver) = &tournament_state.receiver else {
+        return;
+    };
+
+    let mut finished = false;
+    for update in receiver.try_iter() {
+        for mut text in &mut text_query {
+            match &update {
+                TournamentUpdate::Game { description, crosstable } => *text = Text::new(format!("{description}\n\n{crosstable}")),
+                TournamentUpdate::Finished => text.0.push_str("\nTournament complete"),
+                TournamentUpdate::Error(e) => *text = Text::new(format!("Tournament error: {e}")),
+            }
+        }
+
+        finished |= !matches!(update, TournamentUpdate::Game { .. });
+    }
+
+    // Allow another tournament to be started once this one has finished
+    if finished {
+        tournament_state.receiver = None;
+    }
+}
@@ -7,22 +7,29 @@ use std::{
     sync::{mpsc, Arc, Mutex, OnceLock},
 };
 
-use chess_core::{
-    board::{Player, DEFAULT_FEN},
-    piece_move::PieceMove,
-};
+use chess_core::{board::Player, piece_move::PieceMove};
 
 use crate::{
-    classification::classify_move,
+    board::{opponent, player_to_move_at_ply, start_fen},
+    book::{book_depth, OpeningBook},
+    classification::{classify_move, MoveClassification},
+    eval_cache::{board_after_moves, fen_key, position_key, CachedSearch, EvalCache, EvalCacheError},
+    eval_graph::{EvalHistory, PlyRecord},
+    hint::HINT_DEPTH,
+    perft::debug_perft,
+    threat::{null_move_fen, THREAT_DEPTH},
     uci_event::{UciToBoardMessage, UciToBoardReceiver},
     uci_info::{send_uci_info, uci_parse_info, UciEval},
 };
 
-const ENGINE_COMMAND: &str = "stockfish";
+pub const ENGINE_COMMAND: &str = "stockfish";
 // const ENGINE_COMMAND: &str = "target/debug/chess_engine";
 
 pub const ENGINE_PLAYER: Player = Player::Black;
 
+/// Depth used when evaluating and classifying moves
+pub const REVIEW_DEPTH: u32 = 10;
+
 const SHOW_UCI_OUTPUT: bool = false;
 
 static UCI_TX: OnceLock<Mutex<Option<mpsc::Sender<UciMessage>>>> = OnceLock::new();
@@ -53,11 +60,23 @@ pub enum UciError {
     #[error("Engine process could not be waited on")]
     EngineProcessWaitError,
 
+    #[error("Engine \"{0}\" could not be started:\n\t{1}")]
+    EngineStartError(String, std::io::Error),
+
+    #[error("Engine command was empty")]
+    EngineCommandEmpty,
+
+    #[error("Engine stdin/stdout could not be opened")]
+    EngineStdInOutMissing,
+
     #[error("Integer couldn't be parsed:\n\t{0}")]
     NumericalParseError(#[from] std::num::ParseIntError),
 
     #[error("Move history was empty")]
     MoveHistoryEmpty,
+
+    #[error(transparent)]
+    EvalCacheError(#[from] EvalCacheError),
 }
 
 #[derive(Debug, Clone)]
@@ -65,28 +84,236 @@ pub enum UciMessage {
     NewMove { move_history: String, player_to_move: Player },
     UpdateEval { move_history: String, player_to_move: Player },
     ClassifyMove { move_history: String, player_to_move: Player },
+    ReviewGame { move_history: String },
+    Hint { move_history: String },
+    Threat { move_history: String },
+    Perft { move_history: String, depth: u32 },
     CloseChannel,
 }
 
+/// A running engine process which is communicated with via UCI
+pub struct UciEngine {
+    pub stdin: Arc<Mutex<ChildStdin>>,
+    pub stdout_reader: BufReader<ChildStdout>,
+    pub process: Child,
+}
+
+impl UciEngine {
+    /// Start the engine process, returning once it is ready for moves
+    /// ``command`` can include arguments, such as ``./engine --threads 1``, which are split on whitespace
+    /// # Errors
+    /// Returns an error if the command is empty, or the engine process cannot be started
+    /// Returns an error if the engine does not respond to the UCI greeting
+    pub fn start(command: &str) -> Result<Self, UciError> {
+        let mut words = command.split_whitespace();
+        let program = words.next().ok_or(UciError::EngineCommandEmpty)?;
+
+        let mut process = Command::new(program)
+            .args(words)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|e| UciError::EngineStartError(command.to_string(), e))?;
+
+        let stdin = process.stdin.take().ok_or(UciError::EngineStdInOutMissing)?;
+        let stdout = process.stdout.take().ok_or(UciError::EngineStdInOutMissing)?;
+
+        let mut engine = Self {
+            // Create a shared stdin
+            stdin: Arc::new(Mutex::new(stdin)),
+            stdout_reader: BufReader::new(stdout),
+            process,
+        };
+
+        // This returns when the engine has responded that it is ready for moves
+        greet_uci(&engine.stdin, &mut engine.stdout_reader)?;
+
+        Ok(engine)
+    }
+
+    /// Search the position after ``move_history`` to ``depth``, unless the cache already has a search that deep for this position
+    /// # Errors
+    /// Returns an error if the moves in ``move_history`` can't be parsed
+    /// Returns an error if the engine can't be communicated with, or its reply can't be parsed
+    /// Returns an error if the search can't be saved to the cache
+    pub fn search(&mut self, eval_cache: &mut EvalCache, move_history: &str, depth: u32) -> Result<CachedSearch, UciError> {
+        uci_search_position_cached(
+            &self.stdin,
+            &mut self.stdout_reader,
+            eval_cache,
+            position_key(move_history)?,
+            &format!("fen {} moves {move_history}", start_fen()),
+            depth,
+        )
+    }
+
+    /// Search the position given by ``fen`` to ``depth``, unless the cache already has a search that deep for this position
+    /// # Errors
+    /// Returns an error if the engine can't be communicated with, or its reply can't be parsed
+    /// Returns an error if the search can't be saved to the cache
+    pub fn search_fen(&mut self, eval_cache: &mut EvalCache, fen: &str, depth: u32) -> Result<CachedSearch, UciError> {
+        uci_search_position_cached(
+            &self.stdin,
+            &mut self.stdout_reader,
+            eval_cache,
+            fen_key(fen),
+            &format!("fen {fen}"),
+            depth,
+        )
+    }
+
+    /// Evaluate the position after every ply of ``moves``, classifying each move against the eval before it
+    /// Moves which are in ``book`` are classified as book moves instead
+    /// ``on_ply`` is called with the record of each ply as soon as it has been evaluated
+    /// # Errors
+    /// Returns an error if any position can't be searched
+    /// Returns an error if ``on_ply`` returns an error
+    pub fn review<S: AsRef<str>>(
+        &mut self,
+        eval_cache: &mut EvalCache,
+        moves: &[S],
+        depth: u32,
+        book: Option<&OpeningBook>,
+        mut on_ply: impl FnMut(usize, &PlyRecord) -> Result<(), UciError>,
+    ) -> Result<EvalHistory, UciError> {
+        let mut eval_history = EvalHistory::default();
+        let mut previous_eval = None;
+        let mut previous_move_history = String::new();
+
+        for ply in 0..=moves.len() {
+            let move_history = moves[0..ply].iter().map(AsRef::as_ref).collect::<Vec<_>>().join(" ");
+            let search = self.search(eval_cache, &move_history, depth)?;
+
+            let eval = search.info.eval.to_white_perspective(player_to_move_at_ply(ply));
+
+            eval_history.set_eval(ply, eval);
+            eval_history.set_best_line(ply, search.info.pv);
+
+            if ply > 0 {
+                eval_history.set_played_move(ply, moves[ply - 1].as_ref().to_string());
+            }
+
+            if let Some(previous_eval) = previous_eval {
+                let move_class = match book {
+                    Some(book) if book.is_book_move(&board_after_moves(&previous_move_history)?, moves[ply - 1].as_ref()) => {
+                        MoveClassification::Book
+                    }
+                    _ => classify_move(eval, previous_eval),
+                };
+
+                eval_history.set_classification(ply, move_class);
+            }
+
+            if let Some(record) = eval_history.get(ply) {
+                on_ply(ply, record)?;
+            }
+
+            previous_eval = Some(eval);
+            previous_move_history = move_history;
+        }
+
+        Ok(eval_history)
+    }
+
+    /// Search ``position`` (The arguments of a UCI ``position`` command) using ``go_command``, without using the cache
+    /// # Errors
+    /// Returns an error if the engine can't be communicated with, or its reply can't be parsed
+    pub fn go(&mut self, position: &str, go_command: &str) -> Result<CachedSearch, UciError> {
+        self.go_watching(position, go_command, |_| {})
+    }
+
+    /// Search ``position`` using ``go_command`` like ``go``, calling ``on_line`` with every line the engine replies with while searching
+    /// # Errors
+    /// Returns an error if the engine can't be communicated with, or its reply can't be parsed
+    pub fn go_watching(
+        &mut self,
+        position: &str,
+        go_command: &str,
+        mut on_line: impl FnMut(&str),
+    ) -> Result<CachedSearch, UciError> {
+        lock_std_and_write(&self.stdin, format!("position {position}"))?;
+        uci_is_ready_and_wait(&self.stdin, &mut self.stdout_reader)?;
+
+        parse_search_lines(&uci_send_message_and_wait_for(
+            &self.stdin,
+            &mut self.stdout_reader,
+            go_command,
+            |line| {
+                on_line(line);
+
+                line.split_whitespace().next() == Some("bestmove")
+            },
+        )?)
+    }
+
+    /// Count the leaf nodes after each move in ``position`` to ``depth`` with ``go perft`` (Empty if the engine doesn't support it)
+    /// # Errors
+    /// Returns an error if the engine can't be communicated with
+    pub fn perft(&mut self, position: &str, depth: u32) -> Result<Vec<(String, u64)>, UciError> {
+        lock_std_and_write(&self.stdin, format!("position {position}"))?;
+        uci_is_ready_and_wait(&self.stdin, &mut self.stdout_reader)?;
+
+        // Engines without perft ignore the command, so also ask if the engine is ready to know when to stop waiting
+        lock_std_and_write(&self.stdin, format!("go perft {depth}"))?;
+
+        let mut divide = Vec::new();
+        let last_lines = uci_send_message_and_wait_for(&self.stdin, &mut self.stdout_reader, "isready", |line| {
+            if line.starts_with("Nodes searched") {
+                return true;
+            }
+
+            // Each move is replied with as "e2e4: 20"
+            if let Some((algebraic, nodes)) = line.split_once(':') {
+                if let Ok(nodes) = nodes.trim().parse() {
+                    divide.push((algebraic.trim().to_string(), nodes));
+                }
+            }
+
+            line == "readyok"
+        })?;
+
+        // The engine finished the perft before replying to the ready check
+        if last_lines.first().is_some_and(|line| line.starts_with("Nodes searched")) {
+            let mut line = String::new();
+            while line.trim() != "readyok" {
+                line.clear();
+                self.stdout_reader.read_line(&mut line)?;
+            }
+        }
+
+        divide.sort();
+        Ok(divide)
+    }
+
+    /// Tell the engine that the next search is from a different game
+    /// # Errors
+    /// Returns an error if the engine can't be communicated with
+    pub fn new_game(&mut self) -> Result<(), UciError> {
+        lock_std_and_write(&self.stdin, "ucinewgame")?;
+
+        uci_is_ready_and_wait(&self.stdin, &mut self.stdout_reader)
+    }
+
+    /// Tell the engine to exit, then wait for it to close
+    /// # Errors
+    /// Returns an error if the engine can't be wrote to, or waited on
+    pub fn quit(&mut self) -> Result<(), UciError> {
+        // Tell the engine to soft exit
+        lock_std_and_write(&self.stdin, "quit")?;
+
+        // Wait for the engine process to close
+        self.process.wait().map_err(|_| UciError::EngineProcessWaitError)?;
+
+        Ok(())
+    }
+}
+
 /// # Panics
 /// Panics if the engine process cannot start
+/// Panics if the opening book exists, but can't be loaded
 pub fn communicate_to_uci() -> UciToBoardReceiver {
     // Start the engine process // TODO This will break when the binary is moved
-    let mut engine_process = Command::new(ENGINE_COMMAND)
-        .stdin(Stdio::piped())
-        .stdout(Stdio::piped())
-        .spawn()
-        .expect("Failed to start engine");
-
-    let engine_stdin = engine_process.stdin.take().expect("Failed to open stdin");
-    let engine_stdout = engine_process.stdout.take().expect("Failed to open stdout");
-    let mut reader = BufReader::new(engine_stdout);
-
-    // Create a shared stdin
-    let shared_stdin = Arc::new(Mutex::new(engine_stdin));
-
-    // This returns when the engine has responded that it is ready for moves
-    greet_uci(&shared_stdin, &mut reader).unwrap_or_else(|e| panic!("{e}"));
+    let mut engine = UciEngine::start(ENGINE_COMMAND).unwrap_or_else(|e| panic!("{e}"));
 
     // Create a channel to communicate with this process when it is listening to the engine
     let (uci_tx, uci_rx) = std::sync::mpsc::channel();
@@ -97,12 +324,16 @@ pub fn communicate_to_uci() -> UciToBoardReceiver {
     // Create a channel for the engine listener to send messages to Bevy via events
     let (board_tx, board_rx) = crossbeam_channel::unbounded();
 
+    // Searches which have already been performed, so the engine doesn't need to repeat them
+    let mut eval_cache = EvalCache::load().unwrap_or_else(|e| panic!("{e}"));
+
+    // The engine plays from the opening book while it can (If there is one)
+    let book = OpeningBook::load_configured().unwrap_or_else(|e| panic!("{e}"));
+
     // Create a thread for parsing the UciMessages and sending them to the engine
-    let shared_stdin_clone = shared_stdin.clone();
     std::thread::spawn(move || {
         for message in uci_rx {
-            match_uci_message(message, &shared_stdin_clone, &mut reader, &board_tx, &mut engine_process)
-                .unwrap_or_else(|e| panic!("{e}"));
+            match_uci_message(message, &mut engine, &board_tx, &mut eval_cache, book.as_ref()).unwrap_or_else(|e| panic!("{e}"));
         }
 
         println!("Mpsc Channel Closed");
@@ -111,68 +342,76 @@ pub fn communicate_to_uci() -> UciToBoardReceiver {
     UciToBoardReceiver(board_rx)
 }
 
-/// # Panics
-/// Panics if the best move reply can't be parsed
 /// # Errors
 /// Returns an error if the stdin cant be locked, flushed, or wrote to
 /// Returns an error if the stdout reader cannot read a line
+/// Returns an error if the best move reply can't be parsed
 /// Returns an error if mpsc channel cannot be closed
 /// Returns an error if the engine process cannot be waited on
+#[allow(clippy::too_many_lines)]
 pub fn match_uci_message(
     message: UciMessage,
-    shared_stdin: &Arc<Mutex<ChildStdin>>,
-    stdout_reader: &mut BufReader<ChildStdout>,
+    engine: &mut UciEngine,
     board_tx: &crossbeam_channel::Sender<UciToBoardMessage>,
-    engine_process: &mut Child,
+    eval_cache: &mut EvalCache,
+    book: Option<&OpeningBook>,
 ) -> Result<(), UciError> {
     match message {
         UciMessage::NewMove {
             move_history,
             player_to_move,
         } => {
-            lock_std_and_write(
-                shared_stdin,
-                // format!("position startpos moves {move_history}"),
-                format!("position fen {DEFAULT_FEN} moves {move_history}"),
-            )?;
-
-            // Read and print engine output until it reports "readyok"
-            uci_is_ready_and_wait(shared_stdin, stdout_reader)?;
+            // Play a book move instead of searching while the game is still in the book
+            if move_history.split_whitespace().count() < book_depth() {
+                if let Some(book_move) = book.and_then(|book| book.choose_move(&board_after_moves(&move_history).ok()?)) {
+                    let piece_move = PieceMove::from_algebraic(&book_move).map_err(UciError::PieceMoveParseError)?;
+
+                    board_tx.send(UciToBoardMessage::BestMove(piece_move))?;
+
+                    // Book moves aren't searched, so evaluate the position they reach to keep the eval bar and graph up to date
+                    let move_history_after = format!("{move_history} {book_move}");
+                    let search = engine.search(eval_cache, move_history_after.trim_start(), REVIEW_DEPTH)?;
+
+                    send_uci_info(
+                        &search.info,
+                        board_tx,
+                        opponent(player_to_move),
+                        move_history_after.split_whitespace().count(),
+                    )?;
+
+                    return Ok(());
+                }
+            }
 
             // Tell the engine to find the best move
-            let lines = uci_send_message_and_wait_for(shared_stdin, stdout_reader, "go depth 20", |line| {
-                line.split_whitespace().next() == Some("bestmove")
-            })?;
+            let search = engine.search(eval_cache, &move_history, 20)?;
 
             // Convert best move string into the equivalent PieceMove
-            let move_part = lines[0]
-                .trim()
-                .trim_start_matches("bestmove")
-                .split_whitespace()
-                .next()
-                .expect("Could not parse algebraic piece move from bestmove reply");
-            let piece_move = PieceMove::from_algebraic(move_part).map_err(UciError::PieceMoveParseError)?;
+            let piece_move = PieceMove::from_algebraic(&search.best_move).map_err(UciError::PieceMoveParseError)?;
 
             // Send this move to the board
             board_tx.send(UciToBoardMessage::BestMove(piece_move))?;
 
-            send_uci_info(lines[1].as_str(), board_tx, player_to_move)?;
+            send_uci_info(
+                &search.info,
+                board_tx,
+                player_to_move,
+                move_history.split_whitespace().count(),
+            )?;
         }
         UciMessage::UpdateEval {
             move_history,
             player_to_move,
         } => {
-            lock_std_and_write(shared_stdin, format!("position fen {DEFAULT_FEN} moves {move_history}"))?;
-
-            // Read and print engine output until it reports "readyok"
-            uci_is_ready_and_wait(shared_stdin, stdout_reader)?;
-
             // Tell the engine to find the best move (but we only care about the information given before the best move)
-            let lines = uci_send_message_and_wait_for(shared_stdin, stdout_reader, "go depth 10", |line| {
-                line.split_whitespace().next() == Some("bestmove")
-            })?;
+            let search = engine.search(eval_cache, &move_history, REVIEW_DEPTH)?;
 
-            send_uci_info(lines[1].as_str(), board_tx, player_to_move)?;
+            send_uci_info(
+                &search.info,
+                board_tx,
+                player_to_move,
+                move_history.split_whitespace().count(),
+            )?;
         }
         UciMessage::ClassifyMove {
             move_history,
@@ -186,68 +425,179 @@ pub fn match_uci_message(
 
             let move_history_without_final = move_history_split[0..(move_history_split.len() - 1)].join(" ");
 
-            lock_std_and_write(shared_stdin, format!("position fen {DEFAULT_FEN} moves {move_history}"))?;
+            // Tell the engine to find the best move (but we only care about the information given before the best move)
+            let search = engine.search(eval_cache, &move_history, REVIEW_DEPTH)?;
+
+            // Flip the eval if black was moving since the eval is always from the current player's perspective
+            let player_modifier = if player_to_move == Player::Black { -1 } else { 1 };
+            let eval_after_move = search.info.eval.to_white_perspective(player_to_move);
 
-            // Read and print engine output until it reports "readyok"
-            uci_is_ready_and_wait(shared_stdin, stdout_reader)?;
+            let ply = move_history_split.len();
+            board_tx.send(UciToBoardMessage::PlyBestLine { ply, pv: search.info.pv })?;
 
-            // Tell the engine to find the best move (but we only care about the information given before the best move)
-            let lines = uci_send_message_and_wait_for(shared_stdin, stdout_reader, "go depth 10", |line| {
-                line.split_whitespace().next() == Some("bestmove")
+            // Check for the best move in this position
+            let search = engine.search(eval_cache, &move_history_without_final, REVIEW_DEPTH)?;
+
+            board_tx.send(UciToBoardMessage::PlyBestLine {
+                ply: ply - 1,
+                pv: search.info.pv.clone(),
             })?;
 
-            // Parse the final info line from the UCI reply
-            let uci_info = uci_parse_info(lines[1].trim())?;
+            let eval_after_best = match search.info.eval {
+                UciEval::Centipawn(eval) => UciEval::Centipawn(-player_modifier * eval),
+                UciEval::Mate(mate_in) => UciEval::Mate(-player_modifier * mate_in),
+            };
 
-            // Flip the eval if black was moving since the eval is always from the current player's perspective
-            let player_modifier = if player_to_move == Player::Black { -1 } else { 1 };
-            let eval_after_move = match uci_info.eval {
-                UciEval::Centipawn(eval) => UciEval::Centipawn(player_modifier * eval),
-                UciEval::Mate(mate_in) => UciEval::Mate(player_modifier * mate_in),
+            // Moves from the opening book are marked as book moves, rather than being compared to the engine's move
+            let in_book = match book {
+                Some(book) => book.is_book_move(&board_after_moves(&move_history_without_final)?, move_history_split[ply - 1]),
+                None => false,
+            };
+            let move_class = if in_book {
+                MoveClassification::Book
+            } else {
+                classify_move(eval_after_move, eval_after_best)
             };
 
-            // Check for the best move in this position
-            lock_std_and_write(
-                shared_stdin,
-                format!("position fen {DEFAULT_FEN} moves {move_history_without_final }"),
-            )?;
+            // Remember the eval after this move, along with its classification
+            board_tx.send(UciToBoardMessage::PlyEval {
+                ply,
+                eval: eval_after_move,
+            })?;
+            board_tx.send(UciToBoardMessage::MoveClassification { ply, move_class })?;
+        }
+        UciMessage::ReviewGame { move_history } => {
+            let move_history_split = move_history.split_whitespace().collect::<Vec<_>>();
 
-            // Read and print engine output until it reports "readyok"
-            uci_is_ready_and_wait(shared_stdin, stdout_reader)?;
+            // Send the record of each ply to the board as soon as it has been evaluated
+            engine.review(eval_cache, &move_history_split, REVIEW_DEPTH, book, |ply, record| {
+                if let Some(eval) = record.eval {
+                    board_tx.send(UciToBoardMessage::PlyEval { ply, eval })?;
+                }
 
-            // Tell the engine to find the best move (but we only care about the information given before the best move)
-            let lines = uci_send_message_and_wait_for(shared_stdin, stdout_reader, "go depth 10", |line| {
-                line.split_whitespace().next() == Some("bestmove")
+                board_tx.send(UciToBoardMessage::PlyBestLine {
+                    ply,
+                    pv: record.best_line.clone(),
+                })?;
+
+                if let Some(move_class) = record.classification {
+                    board_tx.send(UciToBoardMessage::MoveClassification { ply, move_class })?;
+                }
+
+                Ok(())
             })?;
 
-            // Parse the final info line from the UCI reply
-            let uci_info = uci_parse_info(lines[1].trim())?;
+            board_tx.send(UciToBoardMessage::ReviewFinished)?;
+        }
+        UciMessage::Hint { move_history } => {
+            // Find the best move for the player to move, without playing it
+            let search = engine.search(eval_cache, &move_history, HINT_DEPTH)?;
+
+            // The engine replies "(none)" when the game has ended, so there is no hint
+            let piece_move = PieceMove::from_algebraic(&search.best_move).ok();
 
-            let eval_after_best = match uci_info.eval {
-                UciEval::Centipawn(eval) => UciEval::Centipawn(-player_modifier * eval),
-                UciEval::Mate(mate_in) => UciEval::Mate(-player_modifier * mate_in),
+            board_tx.send(UciToBoardMessage::Hint {
+                move_history,
+                piece_move,
+            })?;
+        }
+        UciMessage::Threat { move_history } => {
+            // Find the opponent's best move as if it were their turn (There is no threat to show if the player to move is in check)
+            let piece_move = match null_move_fen(&move_history)? {
+                Some(fen) => PieceMove::from_algebraic(&engine.search_fen(eval_cache, &fen, THREAT_DEPTH)?.best_move).ok(),
+                None => None,
             };
 
-            let move_class = classify_move(eval_after_move, eval_after_best);
+            board_tx.send(UciToBoardMessage::Threat {
+                move_history,
+                piece_move,
+            })?;
+        }
+        UciMessage::Perft { move_history, depth } => {
+            // Check chess_core's move generation in this position against the engine's
+            let board = board_after_moves(&move_history)?;
 
-            // The eval is in centipawns
-            board_tx.send(UciToBoardMessage::MoveClassification(move_class))?;
+            debug_perft(engine, &board, &format!("fen {} moves {move_history}", start_fen()), depth)?;
         }
         UciMessage::CloseChannel => {
             // Close the channel
             close_uci_channel()?;
 
-            // Tell the engine to soft exit
-            lock_std_and_write(shared_stdin, "quit")?;
-
-            // Wait for the engine process to close
-            engine_process.wait().map_err(|_| UciError::EngineProcessWaitError)?;
+            engine.quit()?;
         }
     }
 
     Ok(())
 }
 
+/// Search ``position`` (The arguments of a UCI ``position`` command) to ``depth``, unless the cache already has a search that deep for ``key``
+/// # Errors
+/// Returns an error if the engine can't be communicated with
+/// Returns an error if the engine's reply can't be parsed
+/// Returns an error if the search can't be saved to the cache
+pub fn uci_search_position_cached(
+    shared_stdin: &Arc<Mutex<ChildStdin>>,
+    stdout_reader: &mut BufReader<ChildStdout>,
+    eval_cache: &mut EvalCache,
+    key: String,
+    position: &str,
+    depth: u32,
+) -> Result<CachedSearch, UciError> {
+    if let Some(search) = eval_cache.get(&key, depth) {
+        return Ok(search.clone());
+    }
+
+    let search = parse_search_lines(&uci_search_position(
+        shared_stdin,
+        stdout_reader,
+        position,
+        &format!("go depth {depth}"),
+    )?)?;
+
+    eval_cache.insert(key, search.clone())?;
+
+    Ok(search)
+}
+
+/// Parse the ``bestmove`` line and the final info line returned by ``uci_search_position``
+/// # Errors
+/// Returns an error if the best move or the info can't be parsed
+pub fn parse_search_lines(lines: &[String]) -> Result<CachedSearch, UciError> {
+    let best_move = lines[0]
+        .trim()
+        .trim_start_matches("bestmove")
+        .split_whitespace()
+        .next()
+        .ok_or_else(|| UciError::PieceMoveParseError(lines[0].clone()))?
+        .to_string();
+
+    Ok(CachedSearch {
+        info: uci_parse_info(lines[1].trim())?,
+        best_move,
+    })
+}
+
+/// Set up ``position`` (The arguments of a UCI ``position`` command), then search it using ``go_command``, returning the ``bestmove`` line and the final info line
+/// # Errors
+/// Returns an error if the stdin can't be locked, wrote to, and flushed
+/// Returns an error if the stdout reader can't read a line
+pub fn uci_search_position(
+    shared_stdin: &Arc<Mutex<ChildStdin>>,
+    stdout_reader: &mut BufReader<ChildStdout>,
+    position: &str,
+    go_command: &str,
+) -> Result<Vec<String>, UciError> {
+    lock_std_and_write(shared_stdin, format!("position {position}"))?;
+
+    // Read and print engine output until it reports "readyok"
+    uci_is_ready_and_wait(shared_stdin, stdout_reader)?;
+
+    // Tell the engine to find the best move
+    uci_send_message_and_wait_for(shared_stdin, stdout_reader, go_command, |line| {
+        line.split_whitespace().next() == Some("bestmove")
+    })
+}
+
 /// # Errors
 /// Returns an error if the Stdin or Stdout cannot be flushed
 pub fn greet_uci(stdin: &Arc<Mutex<ChildStdin>>, stdout_reader: &mut BufReader<ChildStdout>) -> Result<(), UciError> {
@@ -311,7 +661,7 @@ pub fn uci_send_message_and_wait_for(
     stdin: &Arc<Mutex<ChildStdin>>,
     stdout_reader: &mut BufReader<ChildStdout>,
     message: &str,
-    wait_function: impl Fn(&str) -> bool,
+    mut wait_function: impl FnMut(&str) -> bool,
 ) -> Result<Vec<String>, UciError> {
     // Write a message to stdin
     lock_std_and_write(stdin, message)?;
@@ -5,10 +5,14 @@ use chess_core::piece_move::PieceMove;
 use crate::{
     board::BoardBevy,
     classification::{clear_classifications, show_classification, MoveClassification, MoveClassificationMarker},
-    display::BackgroundColourEvent,
+    display::{BackgroundColourEvent, BoardOrientation},
     eval_bar::CurrentEval,
+    eval_graph::EvalHistory,
     game_end::GameEndEvent,
+    hint::{Hint, HintState},
     last_move::LastMoveEvent,
+    puzzle::{export_puzzles, PUZZLE_FILE},
+    threat::{Threat, ThreatState},
     uci_info::UciEval,
 };
 
@@ -17,7 +21,27 @@ pub enum UciToBoardMessage {
     BestMove(PieceMove),
     Centipawn(i32),
     Mate(i32),
-    MoveClassification(MoveClassification),
+    PlyEval {
+        ply: usize,
+        eval: UciEval,
+    },
+    PlyBestLine {
+        ply: usize,
+        pv: Vec<String>,
+    },
+    MoveClassification {
+        ply: usize,
+        move_class: MoveClassification,
+    },
+    ReviewFinished,
+    Hint {
+        move_history: String,
+        piece_move: Option<PieceMove>,
+    },
+    Threat {
+        move_history: String,
+        piece_move: Option<PieceMove>,
+    },
     ClearClassifications,
 }
 
@@ -31,6 +55,11 @@ impl UciEvent {
     pub const fn new(message: UciToBoardMessage) -> Self {
         Self { message }
     }
+
+    #[must_use]
+    pub const fn message(&self) -> &UciToBoardMessage {
+        &self.message
+    }
 }
 
 #[derive(Resource)]
@@ -48,9 +77,13 @@ pub fn uci_to_board_event_handler(
     mut game_end_ev: EventWriter<GameEndEvent>,
     mut last_move_ev: EventWriter<LastMoveEvent>,
     mut current_eval: ResMut<CurrentEval>,
+    mut eval_history: ResMut<EvalHistory>,
+    mut hint_state: ResMut<HintState>,
+    mut threat_state: ResMut<ThreatState>,
     move_classification_entities: Query<Entity, With<MoveClassificationMarker>>,
     asset_server: Res<AssetServer>,
     mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+    orientation: Res<BoardOrientation>,
 ) {
     // Listen for messages from the Engine Listener thread, then apply moves
     for ev in ev_uci_to_board.read() {
@@ -64,8 +97,17 @@ pub fn uci_to_board_event_handler(
                     &mut background_ev,
                     &mut game_end_ev,
                     &mut last_move_ev,
+                    *orientation,
                     piece_move,
                 );
+
+                // Any evals stored for plies after this move belong to a different line
+                let ply = board.current_ply();
+                eval_history.truncate(ply);
+
+                if let Ok(played_move) = piece_move.to_algebraic() {
+                    eval_history.set_played_move(ply, played_move);
+                }
             }
             UciToBoardMessage::Centipawn(eval) => {
                 current_eval.eval = UciEval::Centipawn(eval);
@@ -73,7 +115,20 @@ pub fn uci_to_board_event_handler(
             UciToBoardMessage::Mate(mate_in) => {
                 current_eval.eval = UciEval::Mate(mate_in);
             }
-            UciToBoardMessage::MoveClassification(move_class) => {
+            UciToBoardMessage::PlyEval { ply, eval } => {
+                eval_history.set_eval(ply, eval);
+            }
+            UciToBoardMessage::PlyBestLine { ply, ref pv } => {
+                eval_history.set_best_line(ply, pv.clone());
+            }
+            UciToBoardMessage::MoveClassification { ply, move_class } => {
+                eval_history.set_classification(ply, move_class);
+
+                // Only show a badge if this classification is for the move currently on the board
+                if ply != board.current_ply() {
+                    continue;
+                }
+
                 if let Err(e) = show_classification(
                     &mut commands,
                     &board,
@@ -81,10 +136,40 @@ pub fn uci_to_board_event_handler(
                     &asset_server,
                     &mut texture_atlas_layouts,
                     move_class,
+                    *orientation,
                 ) {
                     eprintln!("Move classification error: {e}");
                 }
             }
+            UciToBoardMessage::ReviewFinished => {
+                // Save any blunders from this game as puzzles
+                match export_puzzles(&eval_history) {
+                    Ok(0) => {}
+                    Ok(puzzle_amt) => println!("Saved {puzzle_amt} new puzzles to {PUZZLE_FILE}"),
+                    Err(e) => eprintln!("Puzzle export error: {e}"),
+                }
+            }
+            UciToBoardMessage::Hint {
+                ref move_history,
+                piece_move,
+            } => {
+                hint_state.pending = None;
+                hint_state.hint = Some(Hint {
+                    move_history: move_history.clone(),
+                    piece_move,
+                    fully_revealed: !hint_state.reveal_piece_first,
+                });
+            }
+            UciToBoardMessage::Threat {
+                ref move_history,
+                piece_move,
+            } => {
+                threat_state.pending = None;
+                threat_state.threat = Some(Threat {
+                    move_history: move_history.clone(),
+                    piece_move,
+                });
+            }
             UciToBoardMessage::ClearClassifications => {
                 clear_classifications(&mut commands, &move_classification_entities);
             }
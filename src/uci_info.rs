@@ -1,3 +1,5 @@
+use std::fmt;
+
 use chess_core::board::Player;
 
 use crate::{uci::UciError, uci_event::UciToBoardMessage};
@@ -6,9 +8,10 @@ use crate::{uci::UciError, uci_event::UciToBoardMessage};
 pub struct UciInfo {
     pub depth: u32,
     pub eval: UciEval,
+    pub pv: Vec<String>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum UciEval {
     Centipawn(i32),
     Mate(i32),
@@ -22,6 +25,42 @@ impl UciEval {
             _ => Self::Mate(eval_value), // eval_type should only be "mate" here
         }
     }
+
+    /// Convert an eval from the perspective of ``player_to_move`` into one from White's perspective
+    #[must_use]
+    pub const fn to_white_perspective(self, player_to_move: Player) -> Self {
+        // Flip the eval if black was moving since the eval is always from the current player's perspective
+        let player_modifier = if matches!(player_to_move, Player::Black) { -1 } else { 1 };
+
+        match self {
+            Self::Centipawn(eval) => Self::Centipawn(player_modifier * eval),
+            Self::Mate(mate_in) => Self::Mate(player_modifier * mate_in),
+        }
+    }
+
+    /// The expected score (Between 0.0 and 1.0) for White, given this eval from White's perspective
+    #[must_use]
+    pub fn to_expected_score(self) -> f32 {
+        match self {
+            Self::Centipawn(eval) => 1. / (1. + 10_f32.powf(-(eval as f32) / 400.)),
+            Self::Mate(mate_in) => {
+                if mate_in > 0 {
+                    1.
+                } else {
+                    0.
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for UciEval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Centipawn(eval) => write!(f, "{:+.2}", *eval as f32 / 100.),
+            Self::Mate(mate_in) => write!(f, "#{mate_in}"),
+        }
+    }
 }
 
 impl Default for UciEval {
@@ -51,8 +90,7 @@ pub fn uci_parse_info<S: AsRef<str>>(line: S) -> Result<UciInfo, UciError> {
                 i += 3;
             }
             "pv" => {
-                // TODO Principal Variation
-                let _pv_moves = &tokens[i + 1..];
+                uci_info.pv = tokens[i + 1..].iter().map(ToString::to_string).collect();
 
                 break;
             }
@@ -72,21 +110,14 @@ pub fn uci_parse_info<S: AsRef<str>>(line: S) -> Result<UciInfo, UciError> {
 }
 
 /// # Errors
-/// Error if the ``uci_parse_info`` cannot parse the information from uci
-pub fn send_uci_info<S: AsRef<str>>(
-    line: S,
+/// Error if the eval cannot be sent to the board
+pub fn send_uci_info(
+    uci_info: &UciInfo,
     board_tx: &crossbeam_channel::Sender<UciToBoardMessage>,
     player_to_move: Player,
+    ply: usize,
 ) -> Result<(), UciError> {
-    // Parse the final info line from the UCI reply
-    let uci_info = uci_parse_info(line.as_ref().trim())?;
-
-    // Flip the eval if black was moving since the eval is always from the current player's perspective
-    let player_modifier = if player_to_move == Player::Black { -1 } else { 1 };
-    let new_eval = match uci_info.eval {
-        UciEval::Centipawn(eval) => UciEval::Centipawn(player_modifier * eval),
-        UciEval::Mate(mate_in) => UciEval::Mate(player_modifier * mate_in),
-    };
+    let new_eval = uci_info.eval.to_white_perspective(player_to_move);
 
     // The eval is in centipawns
     board_tx.send(match new_eval {
@@ -94,5 +125,12 @@ pub fn send_uci_info<S: AsRef<str>>(
         UciEval::Mate(mate_in) => UciToBoardMessage::Mate(mate_in),
     })?;
 
+    // Remember this eval, and the line the engine found, for the position at this ply
+    board_tx.send(UciToBoardMessage::PlyEval { ply, eval: new_eval })?;
+    board_tx.send(UciToBoardMessage::PlyBestLine {
+        ply,
+        pv: uci_info.pv.clone(),
+    })?;
+
     Ok(())
 }